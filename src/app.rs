@@ -1,25 +1,78 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufWriter, Write},
+    ops::Range,
+    path::Path,
+};
+
 use crate::{
-    conversion_utils::{I24, U24},
+    bookmarks::{bookmarks_path, Bookmark, Bookmarks},
+    byte_source::ByteSource,
+    clipboard::Representation,
+    containers,
+    conversion_utils::{
+        strings::{find_strings, FoundString, StringEncoding},
+        to_bytes::{text_to_bytes, value_to_bytes},
+    },
+    decoder::{Decoder, Value},
+    diff::Diff,
+    dump::{write_values, Output},
+    edit_buffer::EditBuffer,
     enums::{endian::Endian, format::Format, inputmodes::InputMode},
+    navigation::{parse_jump, parse_offset, parse_range},
+    search::{Matches, Pattern},
+    template::{Node, Template, TemplateError},
 };
 
+/// Number of rows the window of values spans.
+pub const WINDOW_ROWS: usize = 30;
+
+/// Number of bytes first read for decoding a template record, more are read
+/// for records that turn out to be longer.
+const TEMPLATE_WINDOW: usize = 1 << 16;
+
+/// State of the panel listing the strings found in the file.
+pub struct StringsPanel {
+    pub encoding: StringEncoding,
+    pub min_length: usize,
+    pub strings: Vec<FoundString>,
+    pub selected: usize,
+}
+
+/// State of the panel showing the structure of a recognized file type.
+pub struct StructurePanel {
+    pub root: Node,
+    pub selected: usize,
+}
+
+/// The record of the template at an offset, kept until the cursor moves or
+/// the file changes so that it isn't decoded for every frame.
+pub struct TemplateRecord {
+    pub offset: usize,
+    endian: Endian,
+    pub result: Result<Node, TemplateError>,
+}
+
+/// State of the panel listing the marks and bookmarks.
+pub struct BookmarksPanel {
+    pub selected: usize,
+}
+
+/// The second file of diff mode and where it differs from the first.
+pub struct DiffPanel {
+    pub file_path: String,
+    pub source: ByteSource,
+    pub diff: Diff,
+    /// Index of the difference that was jumped to last.
+    pub current: Option<usize>,
+}
+
 pub struct App {
-    pub bytes_read: Vec<u8>,
+    pub buffer: EditBuffer,
+    pub file_path: String,
     pub should_quit: bool,
     pub endianess: Endian,
-    pub converted_binary_to_u32: Vec<u32>,
-    pub converted_binary_to_i32: Vec<i32>,
-    pub converted_binary_to_hex: Vec<String>,
-    pub converted_binary_to_i8: Vec<i8>,
-    pub converted_binary_to_u16: Vec<u16>,
-    pub converted_binary_to_i16: Vec<i16>,
-    pub converted_binary_to_u24: Vec<U24>,
-    pub converted_binary_to_i24: Vec<I24>,
-    pub converted_binary_to_u64: Vec<u64>,
-    pub converted_binary_to_i64: Vec<i64>,
-    pub converted_binary_to_f32: Vec<f32>,
-    pub converted_binary_to_f64: Vec<f64>,
-    pub converted_binary_to_ascii: Vec<char>,
     pub start_of_window: usize,
     pub end_of_window: usize,
     pub current_format: Format,
@@ -28,9 +81,154 @@ pub struct App {
     pub input: String,
     pub input_mode: InputMode,
     pub cursor_position: usize,
-    pub max_length: usize,
+    pub bytes_per_row: usize,
+    /// How many bytes the decoding of multi-byte formats is shifted by.
+    pub phase: usize,
+    pub search_matches: Option<Matches>,
+    pub status_message: Option<String>,
+    pub strings_panel: Option<StringsPanel>,
+    /// Offset of the byte the cursor is on, which is also the byte that is
+    /// overwritten next.
+    pub byte_cursor: usize,
+    /// Where the selection of visual mode started.
+    pub selection_anchor: Option<usize>,
+    /// First hex digit of a byte that is being typed in.
+    pub pending_nibble: Option<u8>,
+    pub diff_panel: Option<DiffPanel>,
+    /// First key of a two key command such as `]c`.
+    pub pending_key: Option<char>,
+    pub template: Option<Template>,
+    pub show_template: bool,
+    pub template_record: Option<TemplateRecord>,
+    pub structure_panel: Option<StructurePanel>,
+    /// Whether the file changed since the diff, the strings and the structure
+    /// panel were computed, they are computed again when next needed.
+    pub panels_stale: bool,
+    pub bookmarks: Bookmarks,
+    /// Whether the marks or bookmarks changed while there were unsaved
+    /// edits, so that they are written along with the file.
+    pub bookmarks_pending: bool,
+    pub bookmarks_panel: Option<BookmarksPanel>,
 }
 impl App {
+    /// Number of bytes displayed in a single row of the current format.
+    pub fn row_size(&self) -> usize {
+        match self.current_format {
+            Format::Hex => self.bytes_per_row,
+            format => format.size(),
+        }
+    }
+
+    /// Offset of the first byte that is decoded. Only multi-byte formats are
+    /// shifted, the hexdump always starts at the beginning of the file.
+    pub fn decode_base(&self) -> usize {
+        match self.current_format {
+            Format::Hex => 0,
+            format => self.phase % format.size(),
+        }
+    }
+
+    /// Shifts the decoding of multi-byte formats one byte forward or back, so
+    /// that values which aren't aligned to their size can be read.
+    pub fn shift_phase(&mut self, forward: bool) {
+        let offset = self.current_offset();
+        let size = self.current_format.size();
+        let phase = self.decode_base();
+        self.phase = if forward {
+            (phase + 1) % size
+        } else {
+            (phase + size - 1) % size
+        };
+        self.jump_to_offset(offset);
+        // Bytes before the first decoded value aren't shown anymore
+        self.move_byte_cursor(self.byte_cursor.max(self.decode_base()));
+    }
+
+    /// Byte offset the row at `row` starts at.
+    pub fn row_offset(&self, row: usize) -> usize {
+        self.decode_base() + row * self.row_size()
+    }
+
+    /// Number of rows the current format has.
+    pub fn max_length(&self) -> usize {
+        // In diff mode the longer file can be scrolled to its end
+        let len = match &self.diff_panel {
+            Some(panel) => self.buffer.len().max(panel.source.len()),
+            None => self.buffer.len(),
+        };
+        // The last, partially filled row is shown as well
+        len.saturating_sub(self.decode_base())
+            .div_ceil(self.row_size())
+    }
+
+    /// Number of bytes at the end of the file that are too few to be decoded
+    /// as another value of the current format.
+    pub fn trailing_bytes(&self) -> usize {
+        match self.current_format {
+            Format::Hex => 0,
+            format => self.buffer.len().saturating_sub(self.decode_base()) % format.size(),
+        }
+    }
+
+    /// Switches the hexdump between 8, 16 and 32 bytes per row while keeping
+    /// the first displayed byte in view.
+    pub fn cycle_bytes_per_row(&mut self) {
+        let first_byte = self.current_offset();
+        self.bytes_per_row = match self.bytes_per_row {
+            8 => 16,
+            16 => 32,
+            _ => 8,
+        };
+        let rows = self.end_of_window - self.start_of_window;
+        self.start_of_window = first_byte.saturating_sub(self.decode_base()) / self.row_size();
+        self.end_of_window = self.start_of_window + rows;
+        self.scroll_to_cursor();
+    }
+
+    /// Byte offset of the first displayed row.
+    pub fn current_offset(&self) -> usize {
+        self.row_offset(self.start_of_window)
+    }
+
+    /// Moves the window so that it starts at the row containing `offset`.
+    pub fn jump_to_offset(&mut self, offset: usize) {
+        let row = offset.saturating_sub(self.decode_base()) / self.row_size();
+        // Near the end the window is moved back to stay filled, rows past the
+        // end of the file are left out when it is drawn
+        self.start_of_window = row.min(self.max_length().saturating_sub(WINDOW_ROWS));
+        self.end_of_window = self.start_of_window + WINDOW_ROWS;
+    }
+
+    /// Switches to the format at `index` in `format_list`, keeping the byte
+    /// at the top of the window in view.
+    pub fn select_format(&mut self, index: usize) {
+        let offset = self.current_offset();
+        self.format_list_index = index;
+        self.current_format = self.format_list[index];
+        self.jump_to_offset(offset);
+        self.scroll_to_cursor();
+    }
+
+    /// Bytes backing the rows between `start_of_window` and `end_of_window`,
+    /// followed by the lookahead of the current format.
+    pub fn visible_bytes(&self) -> Cow<'_, [u8]> {
+        self.buffer.read(self.current_offset(), self.visible_len())
+    }
+
+    /// Same bytes as [App::visible_bytes] from the second file of diff mode.
+    pub fn visible_diff_bytes(&self) -> &[u8] {
+        match &self.diff_panel {
+            Some(panel) => panel.source.read(self.current_offset(), self.visible_len()),
+            None => &[],
+        }
+    }
+
+    fn visible_len(&self) -> usize {
+        let size = self.row_size();
+        let rows = self.end_of_window.saturating_sub(self.start_of_window);
+        (rows.saturating_mul(size)).saturating_add(self.current_format.lookahead())
+    }
+
     pub fn move_cursor_left(&mut self) {
         let cursor_position = self.cursor_position;
         let cursor_moved_left = cursor_position.saturating_sub(1);
@@ -43,9 +241,22 @@ impl App {
     }
 
     pub fn enter_char(&mut self, new_char: char) {
-        // Check if the character is numeric and not a space or newline
-        if new_char.is_numeric() && new_char != ' ' && new_char != '\n' {
-            self.input.insert(self.cursor_position, new_char);
+        let accepted = match self.input_mode {
+            InputMode::Search | InputMode::Command | InputMode::Overwrite | InputMode::Insert => {
+                !new_char.is_control()
+            }
+            // Offsets only consist of decimal, hexadecimal and relative notation
+            _ => new_char.is_ascii_hexdigit() || matches!(new_char, 'x' | 'X' | '+' | '-'),
+        };
+        if accepted {
+            // The cursor counts characters, search input may contain
+            // characters that are longer than a byte
+            let index = self
+                .input
+                .char_indices()
+                .nth(self.cursor_position)
+                .map_or(self.input.len(), |(index, _)| index);
+            self.input.insert(index, new_char);
             self.move_cursor_right();
         }
     }
@@ -63,7 +274,7 @@ impl App {
     }
 
     pub fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
-        new_cursor_pos.clamp(0, self.input.len())
+        new_cursor_pos.clamp(0, self.input.chars().count())
     }
 
     pub fn reset_cursor(&mut self) {
@@ -71,20 +282,1131 @@ impl App {
     }
 
     pub fn submit_message(&mut self) {
-        if let Ok(num) = self.input.trim().parse::<usize>() {
-            if num > 0 {
-                let end = num.checked_add(30).unwrap_or(usize::MAX);
-                let start = num.saturating_sub(1);
-                if end > self.max_length {
-                    self.start_of_window = self.max_length.saturating_sub(30);
-                    self.end_of_window = self.max_length + 1;
-                } else {
-                    self.start_of_window = start;
-                    self.end_of_window = end;
+        if let Some(jump) = parse_jump(&self.input) {
+            self.go_to(jump.resolve(self.byte_cursor));
+        }
+        self.input.clear();
+        self.reset_cursor();
+    }
+
+    pub fn submit_search(&mut self) {
+        match Pattern::parse(&self.input, self.endianess) {
+            Ok(pattern) => {
+                let bytes = self.buffer.read(0, self.buffer.len());
+                let matches = Matches::find(&pattern, &bytes, self.byte_cursor);
+                self.status_message = Some(format!(
+                    "{} matches for '{}'",
+                    matches.offsets.len(),
+                    self.input
+                ));
+                if let Some(offset) = matches.current_offset() {
+                    self.go_to(offset);
+                }
+                self.search_matches = Some(matches);
+            }
+            Err(e) => self.status_message = Some(format!("Invalid search: {e}")),
+        }
+        self.input.clear();
+        self.reset_cursor();
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Jumps to the next search match, or the previous one if `forward` is
+    /// false.
+    pub fn jump_to_match(&mut self, forward: bool) {
+        let Some(matches) = self.search_matches.as_mut() else {
+            self.status_message = Some("No previous search".to_string());
+            return;
+        };
+        let offset = if forward {
+            matches.next()
+        } else {
+            matches.previous()
+        };
+        match offset {
+            Some(offset) => {
+                self.status_message = Some(format!(
+                    "Match {} of {} at 0x{:X}",
+                    matches.current + 1,
+                    matches.offsets.len(),
+                    offset
+                ));
+                self.go_to(offset);
+            }
+            None => self.status_message = Some("No matches".to_string()),
+        }
+    }
+
+    /// Returns whether the row at `row` contains part of a search match.
+    pub fn row_has_match(&self, row: usize) -> bool {
+        self.search_matches
+            .as_ref()
+            .is_some_and(|matches| matches.overlaps(self.row_offset(row), self.row_size()))
+    }
+
+    /// Opens the strings panel, or closes it if it is already open.
+    pub fn toggle_strings_panel(&mut self) {
+        if self.strings_panel.take().is_some() {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        self.strings_panel = Some(StringsPanel {
+            encoding: StringEncoding::Ascii,
+            min_length: 4,
+            strings: Vec::new(),
+            selected: 0,
+        });
+        self.refresh_strings();
+        self.input_mode = InputMode::Strings;
+    }
+
+    /// Searches the file again with the current settings of the panel.
+    pub fn refresh_strings(&mut self) {
+        let Some(panel) = self.strings_panel.as_mut() else {
+            return;
+        };
+        let bytes = self.buffer.read(0, self.buffer.len());
+        panel.strings = find_strings(&bytes, panel.encoding, panel.min_length);
+        panel.selected = 0;
+        self.status_message = Some(format!(
+            "{} {:?} strings of at least {} characters",
+            panel.strings.len(),
+            panel.encoding,
+            panel.min_length
+        ));
+    }
+
+    pub fn cycle_string_encoding(&mut self) {
+        if let Some(panel) = self.strings_panel.as_mut() {
+            panel.encoding = match panel.encoding {
+                StringEncoding::Ascii => StringEncoding::Utf16Le,
+                StringEncoding::Utf16Le => StringEncoding::Utf16Be,
+                StringEncoding::Utf16Be => StringEncoding::Ascii,
+            };
+            self.refresh_strings();
+        }
+    }
+
+    pub fn change_min_string_length(&mut self, longer: bool) {
+        if let Some(panel) = self.strings_panel.as_mut() {
+            panel.min_length = if longer {
+                panel.min_length + 1
+            } else {
+                panel.min_length.saturating_sub(1).max(1)
+            };
+            self.refresh_strings();
+        }
+    }
+
+    /// Moves the selection of the strings panel down, or up if `down` is
+    /// false.
+    pub fn select_string(&mut self, down: bool) {
+        self.refresh_panels();
+        if let Some(panel) = self.strings_panel.as_mut() {
+            panel.selected = if down {
+                (panel.selected + 1).min(panel.strings.len().saturating_sub(1))
+            } else {
+                panel.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Moves the main view to the selected string.
+    pub fn jump_to_selected_string(&mut self) {
+        self.refresh_panels();
+        let offset = self
+            .strings_panel
+            .as_ref()
+            .and_then(|panel| panel.strings.get(panel.selected))
+            .map(|found| found.offset);
+        if let Some(offset) = offset {
+            self.go_to(offset);
+        }
+    }
+
+    /// Opens the panel with the structure of the file, if its type is
+    /// recognized, or closes it.
+    pub fn toggle_structure_panel(&mut self) {
+        if self.structure_panel.take().is_some() {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        match containers::parse(&self.buffer.read(0, self.buffer.len())) {
+            Some(root) => {
+                self.structure_panel = Some(StructurePanel { root, selected: 0 });
+                self.input_mode = InputMode::Structure;
+            }
+            None => {
+                self.status_message =
+                    Some("Not a recognized file type (ELF, PNG, ZIP or WAV)".to_string())
+            }
+        }
+    }
+
+    /// Moves the selection of the structure panel down, or up if `down` is
+    /// false.
+    pub fn select_node(&mut self, down: bool) {
+        self.refresh_panels();
+        if let Some(panel) = self.structure_panel.as_mut() {
+            let last = panel.root.flatten().len() - 1;
+            panel.selected = if down {
+                (panel.selected + 1).min(last)
+            } else {
+                panel.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Moves the main view and the byte cursor to the selected node.
+    pub fn jump_to_selected_node(&mut self) {
+        self.refresh_panels();
+        let Some(panel) = &self.structure_panel else {
+            return;
+        };
+        let Some((_, node)) = panel.root.flatten().get(panel.selected).copied() else {
+            return;
+        };
+        let (offset, size) = (node.offset, node.size);
+        if offset >= self.buffer.len() {
+            self.status_message = Some(format!("{offset:#X} is past the end of the file"));
+            return;
+        }
+        self.status_message = Some(format!("{size} bytes at {offset:#X}"));
+        self.go_to(offset);
+    }
+
+    /// Row the byte cursor is in.
+    pub fn cursor_row(&self) -> usize {
+        self.byte_cursor.saturating_sub(self.decode_base()) / self.row_size()
+    }
+
+    /// Number of bytes the cursor moves by sideways: a whole value of number
+    /// formats and a single byte of everything else.
+    pub fn value_size(&self) -> usize {
+        match self.current_format {
+            Format::Hex => 1,
+            format if format.is_text() => 1,
+            format => format.size(),
+        }
+    }
+
+    /// Moves the byte cursor, scrolling the window so that it stays visible.
+    pub fn move_byte_cursor(&mut self, offset: usize) {
+        // Inserting may also append bytes after the last one
+        let last = match self.input_mode {
+            InputMode::Insert => self.buffer.len(),
+            _ => self.buffer.len().saturating_sub(1),
+        };
+        self.byte_cursor = offset.min(last);
+        self.pending_nibble = None;
+        self.scroll_to_cursor();
+    }
+
+    /// Moves the byte cursor to `offset` and the window to the row it is in.
+    pub fn go_to(&mut self, offset: usize) {
+        self.jump_to_offset(offset);
+        self.move_byte_cursor(offset);
+    }
+
+    /// Scrolls the window as little as possible to show the byte cursor.
+    fn scroll_to_cursor(&mut self) {
+        let row = self.cursor_row();
+        let rows = self.end_of_window - self.start_of_window;
+        if row < self.start_of_window {
+            self.start_of_window = row;
+        } else if row >= self.end_of_window {
+            self.start_of_window = row + 1 - rows;
+        }
+        self.end_of_window = self.start_of_window + rows;
+    }
+
+    /// Enters overwrite mode, or insert mode if `insert` is set.
+    pub fn enter_overwrite_mode(&mut self, insert: bool) {
+        self.pending_nibble = None;
+        self.input.clear();
+        self.reset_cursor();
+        self.input_mode = if insert {
+            InputMode::Insert
+        } else {
+            InputMode::Overwrite
+        };
+    }
+
+    /// Overwrites or inserts `bytes` at `offset` depending on the mode and
+    /// moves the byte cursor past them. Overwriting never makes the file
+    /// longer, so a value that doesn't fit before its end is rejected.
+    fn write_bytes(&mut self, offset: usize, bytes: &[u8]) {
+        let len = self.buffer.len();
+        if matches!(self.input_mode, InputMode::Insert) {
+            self.buffer.insert(offset, bytes);
+        } else {
+            let left = len.saturating_sub(offset);
+            if bytes.len() > left {
+                self.status_message = Some(format!(
+                    "Value needs {} bytes, only {left} left",
+                    bytes.len()
+                ));
+                return;
+            }
+            self.buffer.overwrite(offset, bytes);
+        }
+        self.after_edit(offset, len);
+        self.move_byte_cursor(offset + bytes.len());
+    }
+
+    /// Removes up to `len` bytes starting at `offset`.
+    pub fn delete_bytes(&mut self, offset: usize, len: usize) {
+        let old_len = self.buffer.len();
+        self.buffer.delete(offset, len);
+        self.after_edit(offset, old_len);
+        self.move_byte_cursor(offset);
+    }
+
+    /// Offsets found before an edit at `offset` that changed the length of
+    /// the file point at the wrong bytes afterwards, so they are looked up
+    /// again or moved along with their bytes.
+    fn after_edit(&mut self, offset: usize, old_len: usize) {
+        self.panels_stale = true;
+        self.template_record = None;
+        let len = self.buffer.len();
+        if len != old_len && !self.bookmarks.is_empty() {
+            self.bookmarks.shift(
+                offset,
+                old_len.saturating_sub(len),
+                len.saturating_sub(old_len),
+            );
+            self.bookmarks_pending = true;
+        }
+        // Undoing every edit makes the moved marks match the file again
+        if self.bookmarks_pending && !self.buffer.is_modified() {
+            self.save_bookmarks();
+        }
+        if len != old_len && self.search_matches.take().is_some() {
+            self.status_message = Some("Search results cleared, the file changed".to_string());
+        }
+    }
+
+    /// Computes the diff, the strings and the structure panel again if the
+    /// file changed since, which is done once per frame rather than for
+    /// every edit.
+    pub fn refresh_panels(&mut self) {
+        if !std::mem::take(&mut self.panels_stale) {
+            return;
+        }
+        self.refresh_diff();
+        if self.strings_panel.is_none() && self.structure_panel.is_none() {
+            return;
+        }
+        let bytes = self.buffer.read(0, self.buffer.len());
+        if let Some(panel) = self.strings_panel.as_mut() {
+            panel.strings = find_strings(&bytes, panel.encoding, panel.min_length);
+            panel.selected = panel.selected.min(panel.strings.len().saturating_sub(1));
+        }
+        if let Some(panel) = self.structure_panel.as_mut() {
+            match containers::parse(&bytes) {
+                Some(root) => {
+                    panel.selected = panel.selected.min(root.flatten().len() - 1);
+                    panel.root = root;
+                }
+                None => self.structure_panel = None,
+            }
+        }
+    }
+
+    /// Handles a character typed in overwrite or insert mode. The hexdump takes hex
+    /// digits, character formats take characters and number formats collect
+    /// the value until [App::submit_value] is called.
+    pub fn overwrite_char(&mut self, new_char: char) {
+        match self.current_format {
+            Format::Hex => {
+                let Some(digit) = new_char.to_digit(16) else {
+                    return;
+                };
+                match self.pending_nibble.take() {
+                    Some(high) => {
+                        self.write_bytes(self.byte_cursor, &[high << 4 | digit as u8]);
+                    }
+                    None => self.pending_nibble = Some(digit as u8),
                 }
             }
+            format if format.is_text() => match text_to_bytes(&new_char.to_string(), format) {
+                Ok(bytes) => self.write_bytes(self.byte_cursor, &bytes),
+                Err(e) => self.status_message = Some(e.to_string()),
+            },
+            _ => self.enter_char(new_char),
+        }
+    }
+
+    /// Writes the typed value over the value the byte cursor is in, or
+    /// inserts it before the byte cursor in insert mode.
+    pub fn submit_value(&mut self) {
+        match value_to_bytes(&self.input, self.current_format, self.endianess) {
+            Ok(bytes) => {
+                let offset = match self.input_mode {
+                    InputMode::Insert => self.byte_cursor,
+                    _ => self.row_offset(self.cursor_row()),
+                };
+                self.status_message = None;
+                self.write_bytes(offset, &bytes);
+            }
+            Err(e) => self.status_message = Some(e.to_string()),
         }
         self.input.clear();
         self.reset_cursor();
     }
+
+    pub fn undo(&mut self) {
+        let len = self.buffer.len();
+        match self.buffer.undo() {
+            Some(offset) => {
+                self.after_edit(offset, len);
+                self.move_byte_cursor(offset);
+            }
+            None => self.status_message = Some("Already at oldest change".to_string()),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        let len = self.buffer.len();
+        match self.buffer.redo() {
+            Some(offset) => {
+                self.after_edit(offset, len);
+                self.move_byte_cursor(offset);
+            }
+            None => self.status_message = Some("Already at newest change".to_string()),
+        }
+    }
+
+    /// Writes the changes to `path`, or the opened file if [None], and
+    /// continues with the written file.
+    pub fn save(&mut self, path: Option<&str>) -> std::io::Result<()> {
+        let target = path.unwrap_or(&self.file_path).to_string();
+        if target == "-" {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "stdin can't be written to, use :w FILE",
+            ));
+        }
+        let original = (self.file_path != "-").then(|| Path::new(&self.file_path));
+        self.buffer.save(Path::new(&target), original)?;
+        self.buffer = EditBuffer::new(ByteSource::open(&target, Some(self.buffer.len()))?);
+        self.file_path = target;
+        Ok(())
+    }
+
+    /// Saves as [App::save] does and quits afterwards if `quit` is set.
+    fn write(&mut self, path: Option<&str>, quit: bool) {
+        match self.save(path) {
+            Ok(()) => {
+                self.status_message = Some(format!("Saved {}", self.file_path));
+                if self.bookmarks_pending {
+                    self.save_bookmarks();
+                }
+                if quit {
+                    self.quit(false);
+                }
+            }
+            Err(e) => self.status_message = Some(format!("Could not save: {e}")),
+        }
+    }
+
+    /// Runs `:export [plain|csv|json] FILE [RANGE]`: writes the bytes of the
+    /// range, the selection or the whole file to `FILE`, or the values of the
+    /// current format if an output is given.
+    fn export(&mut self, argument: &str) {
+        let mut words = argument.split_whitespace().peekable();
+        let output = words.peek().and_then(|word| word.parse::<Output>().ok());
+        if output.is_some() {
+            words.next();
+        }
+        let Some(path) = words.next() else {
+            self.status_message = Some("Usage: export [plain|csv|json] FILE [RANGE]".to_string());
+            return;
+        };
+        let range = match words.next() {
+            Some(range) => match parse_range(range) {
+                Some(range) => range,
+                None => {
+                    self.status_message = Some(format!(
+                        "Invalid range '{range}', use START..END or START+LENGTH"
+                    ));
+                    return;
+                }
+            },
+            None => self.selection().unwrap_or(0..self.buffer.len()),
+        };
+        if range.end > self.buffer.len() {
+            self.status_message = Some(format!(
+                "{:#X}..{:#X} reaches past the end of the file",
+                range.start, range.end
+            ));
+            return;
+        }
+
+        // The opened files are mapped, truncating one would pull the bytes
+        // out from under the map
+        let target = Path::new(path).canonicalize().ok();
+        let diff_path = self.diff_panel.as_ref().map(|panel| &panel.file_path);
+        let is_open = [Some(&self.file_path), diff_path]
+            .into_iter()
+            .flatten()
+            .any(|open| target.is_some() && Path::new(open).canonicalize().ok() == target);
+        if is_open {
+            self.status_message = Some(format!(
+                "Can't export to {path}, the file is open, use :w to save it"
+            ));
+            return;
+        }
+
+        let bytes = self.buffer.read(range.start, range.len());
+        let written = File::create(path).and_then(|file| {
+            let mut file = BufWriter::new(file);
+            match output {
+                Some(output) => {
+                    let decoder = Decoder::new(&bytes, self.current_format, self.endianess, 0);
+                    let values = decoder.map(|value| Value {
+                        offset: range.start + value.offset,
+                        text: value.text,
+                    });
+                    write_values(&mut file, values, self.current_format, output)?;
+                }
+                None => file.write_all(&bytes)?,
+            }
+            file.flush()
+        });
+        self.status_message = Some(match written {
+            Ok(()) => format!(
+                "Exported {} bytes at {:#X} to {path}",
+                range.len(),
+                range.start
+            ),
+            Err(e) => format!("Could not export to {path}: {e}"),
+        });
+    }
+
+    /// Quits unless there are unsaved changes, or `force` is set.
+    pub fn quit(&mut self, force: bool) {
+        if self.buffer.is_modified() && !force {
+            self.status_message = Some(
+                "There are unsaved changes, use :w to save or :q! to discard them".to_string(),
+            );
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    /// Runs the command typed after `:`.
+    pub fn run_command(&mut self) {
+        let command = std::mem::take(&mut self.input);
+        self.reset_cursor();
+        self.input_mode = InputMode::Normal;
+        let command = command.trim();
+        let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+        let argument = argument.trim();
+        // Commands that take a file write to the opened one without it
+        let path = (!argument.is_empty()).then_some(argument);
+        match name {
+            "d" | "delete" => match parse_offset(argument) {
+                Some(count) => self.delete_bytes(self.byte_cursor, count),
+                None => self.status_message = Some(format!("Invalid byte count '{argument}'")),
+            },
+            "bm" | "bookmark" => self.add_bookmark(argument),
+            "export" => self.export(argument),
+            "template" => match path {
+                Some(path) => self.load_template(path),
+                None => self.status_message = Some("Usage: template FILE".to_string()),
+            },
+            "w" => self.write(path, false),
+            "wq" | "x" => self.write(path, true),
+            "q" => self.quit(false),
+            "q!" => self.quit(true),
+            _ => self.status_message = Some(format!("Unknown command '{name}'")),
+        }
+    }
+
+    /// Compares the file with the second file of diff mode again.
+    pub fn refresh_diff(&mut self) {
+        let Some(panel) = self.diff_panel.as_mut() else {
+            return;
+        };
+        panel.diff = Diff::new(
+            &self.buffer.read(0, self.buffer.len()),
+            panel.source.as_slice(),
+        );
+    }
+
+    /// Returns whether the bytes of `row` differ between the two files of
+    /// diff mode.
+    pub fn row_differs(&self, row: usize) -> bool {
+        self.diff_panel
+            .as_ref()
+            .is_some_and(|panel| panel.diff.overlaps(self.row_offset(row), self.row_size()))
+    }
+
+    /// Moves the cursor to the next or previous difference of diff mode.
+    pub fn jump_to_difference(&mut self, forward: bool) {
+        self.refresh_panels();
+        let cursor = self.byte_cursor;
+        let Some(panel) = &mut self.diff_panel else {
+            self.status_message = Some("Not comparing two files, use --diff".to_string());
+            return;
+        };
+        // Continue from the last difference while the cursor is still on it
+        let last = panel
+            .current
+            .and_then(|index| panel.diff.ranges.get(index))
+            .map(|range| range.start)
+            .filter(|&start| start == cursor);
+        let index = match (last, forward) {
+            (Some(start), true) => panel.diff.next(start),
+            (Some(start), false) => panel.diff.previous(start),
+            // A difference right at the cursor counts as the next one
+            (None, true) => panel.diff.next(cursor.wrapping_sub(1)),
+            (None, false) => panel.diff.previous(cursor),
+        };
+        panel.current = index;
+        let Some(index) = index else {
+            self.status_message = Some("Files are identical".to_string());
+            return;
+        };
+        let range = panel.diff.ranges[index].clone();
+        self.status_message = Some(format!(
+            "Difference {} of {} at {:#X}..{:#X} ({} bytes)",
+            index + 1,
+            panel.diff.ranges.len(),
+            range.start,
+            range.end,
+            range.len()
+        ));
+        self.go_to(range.start);
+    }
+
+    /// Loads the template at `path`, replacing the current one.
+    pub fn load_template(&mut self, path: &str) {
+        let template = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| Template::parse(&source).map_err(|e| e.to_string()));
+        match template {
+            Ok(template) => {
+                self.template = Some(template);
+                self.template_record = None;
+                self.show_template = true;
+                self.status_message = Some(format!("Loaded template {path}"));
+            }
+            Err(e) => self.status_message = Some(format!("Could not load {path}: {e}")),
+        }
+    }
+
+    pub fn toggle_template_panel(&mut self) {
+        if self.template.is_none() {
+            self.status_message =
+                Some("No template loaded, use --template or :template FILE".to_string());
+            return;
+        }
+        self.show_template = !self.show_template;
+    }
+
+    /// Decodes the record at the byte cursor if a template is loaded, unless
+    /// it was decoded there already.
+    pub fn refresh_template_record(&mut self) {
+        let Some(template) = &self.template else {
+            self.template_record = None;
+            return;
+        };
+        let (offset, endian) = (self.byte_cursor, self.endianess);
+        let is_current = self
+            .template_record
+            .as_ref()
+            .is_some_and(|record| record.offset == offset && record.endian == endian);
+        if is_current {
+            return;
+        }
+        let file_len = self.buffer.len();
+        let mut len = TEMPLATE_WINDOW;
+        let result = loop {
+            let window = self.buffer.read(offset, len);
+            match template.decode_window(&window, offset, file_len, endian) {
+                Err(TemplateError::PastWindow(end)) => len = (end - offset).max(len * 2),
+                result => break result,
+            }
+        };
+        self.template_record = Some(TemplateRecord {
+            offset,
+            endian,
+            result,
+        });
+    }
+
+    /// Starts selecting bytes at the byte cursor.
+    pub fn enter_visual_mode(&mut self) {
+        self.selection_anchor = Some(self.byte_cursor);
+        self.input_mode = InputMode::Visual;
+    }
+
+    pub fn leave_visual_mode(&mut self) {
+        self.selection_anchor = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Bytes selected in visual mode, from the anchor up to and including the
+    /// value the byte cursor is on.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.selection_anchor?;
+        let start = anchor.min(self.byte_cursor);
+        let end = anchor.max(self.byte_cursor) + self.value_size();
+        Some(start..end.min(self.buffer.len()))
+    }
+
+    /// Returns whether the row at `row` contains selected bytes.
+    pub fn row_is_selected(&self, row: usize) -> bool {
+        self.selection().is_some_and(|selection| {
+            let start = self.row_offset(row);
+            start < selection.end && selection.start < start + self.row_size()
+        })
+    }
+
+    /// Bytes that are yanked: the selection in visual mode, otherwise the
+    /// value the cursor is on.
+    pub fn yank_range(&self) -> Range<usize> {
+        self.selection().unwrap_or_else(|| {
+            let end = self.byte_cursor.saturating_add(self.value_size());
+            self.byte_cursor..end.min(self.buffer.len())
+        })
+    }
+
+    /// Returns the yanked bytes as `representation` and leaves visual mode.
+    pub fn yank(&mut self, representation: Representation) -> String {
+        let range = self.yank_range();
+        let text = representation.format(&self.buffer.read(range.start, range.len()));
+        self.status_message = Some(format!(
+            "Copied {} bytes at {:#X} as {}",
+            range.len(),
+            range.start,
+            representation.name()
+        ));
+        if self.selection_anchor.is_some() {
+            self.leave_visual_mode();
+        }
+        text
+    }
+
+    /// Removes the selected bytes and leaves visual mode.
+    pub fn delete_selection(&mut self) {
+        if let Some(selection) = self.selection() {
+            self.leave_visual_mode();
+            self.status_message = Some(format!("Deleted {} bytes", selection.len()));
+            self.delete_bytes(selection.start, selection.len());
+        }
+    }
+
+    /// Reads the marks and bookmarks saved for the opened file.
+    pub fn load_bookmarks(&mut self) {
+        if self.file_path == "-" {
+            return;
+        }
+        // Without a place to keep them there is nothing to load, saving
+        // tells why
+        let Ok(path) = bookmarks_path(Path::new(&self.file_path)) else {
+            return;
+        };
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            return;
+        };
+        match Bookmarks::parse(&source) {
+            Ok(bookmarks) => self.bookmarks = bookmarks,
+            Err(e) => self.status_message = Some(format!("Could not read {}: {e}", path.display())),
+        }
+    }
+
+    /// Writes the marks and bookmarks after they changed. With unsaved edits
+    /// their offsets are those of the edited bytes, which only match the file
+    /// once it is saved, so they are written along with it.
+    fn bookmarks_changed(&mut self) {
+        if !self.buffer.is_modified() {
+            self.save_bookmarks();
+            return;
+        }
+        self.bookmarks_pending = true;
+        if let Some(message) = self.status_message.as_mut() {
+            message.push_str(", kept once the file is saved");
+        }
+    }
+
+    /// Keeps the marks and bookmarks for the next time the file is opened.
+    fn save_bookmarks(&mut self) {
+        self.bookmarks_pending = false;
+        if self.file_path == "-" {
+            self.status_message = Some(
+                "Bookmarks of piped bytes can't be kept, save them to a file first".to_string(),
+            );
+            return;
+        }
+        let path = match bookmarks_path(Path::new(&self.file_path)) {
+            Ok(path) => path,
+            Err(e) => {
+                self.status_message = Some(format!("Bookmarks can't be saved, {e}"));
+                return;
+            }
+        };
+        let saved = if self.bookmarks.is_empty() {
+            std::fs::remove_file(&path).or_else(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(()),
+                _ => Err(e),
+            })
+        } else {
+            let toml = self.bookmarks.to_toml(&self.file_path);
+            path.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, toml))
+        };
+        if let Err(e) = saved {
+            self.status_message = Some(format!("Could not save {}: {e}", path.display()));
+        }
+    }
+
+    /// Sets the mark `letter` to the byte cursor.
+    pub fn set_mark(&mut self, letter: char) {
+        self.bookmarks.marks.insert(letter, self.byte_cursor);
+        self.status_message = Some(format!("Mark '{letter} set at {:#X}", self.byte_cursor));
+        self.bookmarks_changed();
+    }
+
+    pub fn jump_to_mark(&mut self, letter: char) {
+        match self.bookmarks.marks.get(&letter) {
+            Some(&offset) => self.go_to(offset),
+            None => self.status_message = Some(format!("Mark '{letter} isn't set")),
+        }
+    }
+
+    /// Runs `:bookmark NAME [COMMENT]`, which bookmarks the byte cursor.
+    fn add_bookmark(&mut self, argument: &str) {
+        let (name, comment) = argument.split_once(' ').unwrap_or((argument, ""));
+        if name.is_empty() {
+            self.status_message = Some("Usage: bookmark NAME [COMMENT]".to_string());
+            return;
+        }
+        self.bookmarks.add(Bookmark {
+            name: name.to_string(),
+            offset: self.byte_cursor,
+            comment: comment.trim().to_string(),
+        });
+        self.status_message = Some(format!("Bookmarked {:#X} as {name}", self.byte_cursor));
+        self.bookmarks_changed();
+    }
+
+    /// Opens the panel listing the marks and bookmarks, or closes it.
+    pub fn toggle_bookmarks_panel(&mut self) {
+        if self.bookmarks_panel.take().is_some() {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        if self.bookmarks.is_empty() {
+            self.status_message = Some(
+                "No bookmarks yet, set a mark with m and a letter or use :bookmark NAME"
+                    .to_string(),
+            );
+            return;
+        }
+        self.bookmarks_panel = Some(BookmarksPanel { selected: 0 });
+        self.input_mode = InputMode::Bookmarks;
+    }
+
+    /// Moves the selection of the bookmarks panel down, or up if `down` is
+    /// false.
+    pub fn select_bookmark(&mut self, down: bool) {
+        let last = self.bookmarks.entries().len().saturating_sub(1);
+        if let Some(panel) = self.bookmarks_panel.as_mut() {
+            panel.selected = if down {
+                (panel.selected + 1).min(last)
+            } else {
+                panel.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    pub fn jump_to_selected_bookmark(&mut self) {
+        let offset = self.bookmarks_panel.as_ref().and_then(|panel| {
+            self.bookmarks
+                .entries()
+                .get(panel.selected)
+                .map(|entry| entry.offset)
+        });
+        if let Some(offset) = offset {
+            self.go_to(offset);
+        }
+    }
+
+    /// Removes the selected mark or bookmark, closing the panel when none
+    /// are left.
+    pub fn delete_selected_bookmark(&mut self) {
+        let Some(panel) = self.bookmarks_panel.as_mut() else {
+            return;
+        };
+        self.bookmarks.remove(panel.selected);
+        panel.selected = panel
+            .selected
+            .min(self.bookmarks.entries().len().saturating_sub(1));
+        if self.bookmarks.is_empty() {
+            self.toggle_bookmarks_panel();
+        }
+        self.bookmarks_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(bytes: &[u8]) -> App {
+        App {
+            buffer: EditBuffer::new(ByteSource::Buffered(bytes.to_vec())),
+            file_path: "-".to_string(),
+            should_quit: false,
+            endianess: Endian::Big,
+            start_of_window: 0,
+            end_of_window: WINDOW_ROWS,
+            current_format: Format::Hex,
+            format_list_index: 0,
+            format_list: vec![Format::Hex],
+            input: String::new(),
+            input_mode: InputMode::Normal,
+            cursor_position: 0,
+            bytes_per_row: 16,
+            phase: 0,
+            search_matches: None,
+            status_message: None,
+            strings_panel: None,
+            byte_cursor: 0,
+            selection_anchor: None,
+            pending_nibble: None,
+            diff_panel: None,
+            pending_key: None,
+            template: None,
+            show_template: false,
+            template_record: None,
+            structure_panel: None,
+            panels_stale: false,
+            bookmarks: Bookmarks::default(),
+            bookmarks_pending: false,
+            bookmarks_panel: None,
+        }
+    }
+
+    fn type_value(app: &mut App, text: &str) {
+        text.chars().for_each(|c| app.overwrite_char(c));
+        app.submit_value();
+    }
+
+    #[test]
+    fn test_insert_float() {
+        let mut app = app(&[0xAA]);
+        app.current_format = Format::F32;
+        app.enter_overwrite_mode(true);
+        type_value(&mut app, "2.75");
+        let mut expected = 2.75f32.to_be_bytes().to_vec();
+        expected.push(0xAA);
+        assert_eq!(&*app.buffer.read(0, 8), &expected[..]);
+        assert_eq!(app.byte_cursor, 4);
+    }
+
+    #[test]
+    fn test_window_keeps_its_rows_when_the_file_grows() {
+        let mut app = app(&[0; 20]);
+        app.go_to(0);
+        assert_eq!((app.start_of_window, app.end_of_window), (0, WINDOW_ROWS));
+        app.enter_overwrite_mode(true);
+        app.move_byte_cursor(20);
+        for _ in 0..200 {
+            "ff".chars().for_each(|c| app.overwrite_char(c));
+        }
+        assert_eq!(app.buffer.len(), 220);
+        assert_eq!(app.end_of_window - app.start_of_window, WINDOW_ROWS);
+        assert!((app.start_of_window..app.end_of_window).contains(&app.cursor_row()));
+    }
+
+    #[test]
+    fn test_overwrite_past_the_end_is_rejected() {
+        let mut app = app(&[0; 6]);
+        app.current_format = Format::Uint32;
+        app.enter_overwrite_mode(false);
+        app.move_byte_cursor(4);
+        type_value(&mut app, "1");
+        assert_eq!(&*app.buffer.read(0, 8), &[0; 6]);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Value needs 4 bytes, only 2 left")
+        );
+        assert!(!app.buffer.is_modified());
+
+        app.move_byte_cursor(0);
+        type_value(&mut app, "1");
+        assert_eq!(&*app.buffer.read(0, 8), &[0, 0, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_export_refuses_the_opened_file() {
+        let path = std::env::temp_dir().join(format!("byteblitz-export-{}", std::process::id()));
+        std::fs::write(&path, [1, 2, 3]).unwrap();
+        let mut app = app(&[1, 2, 3]);
+        app.file_path = path.to_string_lossy().into_owned();
+        app.export(&format!("{} 0+1", app.file_path));
+        assert!(app
+            .status_message
+            .as_deref()
+            .is_some_and(|message| message.starts_with("Can't export")));
+        assert_eq!(std::fs::read(&path).unwrap(), vec![1, 2, 3]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_commands_without_arguments_reach_their_handler() {
+        let mut app = app(&[1, 2, 3]);
+        for (command, status) in [
+            ("export", "Usage: export [plain|csv|json] FILE [RANGE]"),
+            (" bookmark ", "Usage: bookmark NAME [COMMENT]"),
+            ("template", "Usage: template FILE"),
+            ("frobnicate now", "Unknown command 'frobnicate'"),
+        ] {
+            app.input = command.to_string();
+            app.run_command();
+            assert_eq!(app.status_message.as_deref(), Some(status));
+        }
+        app.input = "q".to_string();
+        app.run_command();
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_bookmarks_move_with_their_bytes() {
+        let mut app = app(&[0; 16]);
+        app.bookmarks.marks.insert('a', 2);
+        app.bookmarks.marks.insert('b', 8);
+        app.bookmarks.add(Bookmark {
+            name: "end".to_string(),
+            offset: 12,
+            comment: String::new(),
+        });
+        app.enter_overwrite_mode(true);
+        app.move_byte_cursor(4);
+        "ffff".chars().for_each(|c| app.overwrite_char(c));
+        let offsets = |app: &App| -> Vec<usize> {
+            app.bookmarks
+                .entries()
+                .iter()
+                .map(|entry| entry.offset)
+                .collect()
+        };
+        assert_eq!(offsets(&app), vec![2, 10, 14]);
+        app.delete_bytes(1, 10);
+        assert_eq!(offsets(&app), vec![1, 1, 4]);
+        // Marks on deleted bytes stay with the byte after them
+        app.undo();
+        assert_eq!(offsets(&app), vec![11, 11, 14]);
+    }
+
+    #[test]
+    fn test_shifting_the_phase_keeps_the_cursor_on_decoded_bytes() {
+        let mut app = app(&[0; 16]);
+        app.current_format = Format::Uint32;
+        app.shift_phase(true);
+        app.shift_phase(true);
+        assert_eq!(app.decode_base(), 2);
+        assert_eq!(app.byte_cursor, 2);
+        assert_eq!(app.cursor_row(), 0);
+        app.shift_phase(false);
+        assert_eq!(app.byte_cursor, 2);
+    }
+
+    #[test]
+    fn test_marks_of_unsaved_edits_are_written_with_the_file() {
+        let dir = std::env::temp_dir().join(format!("byteblitz-marks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var("XDG_DATA_HOME", &dir);
+        let path = dir.join("data.bin");
+        std::fs::write(&path, [0; 16]).unwrap();
+        let open = || {
+            let mut app = app(&std::fs::read(&path).unwrap());
+            app.file_path = path.to_string_lossy().into_owned();
+            app.load_bookmarks();
+            app
+        };
+        let marks = |app: &App| -> Vec<(char, usize)> {
+            app.bookmarks.marks.iter().map(|(&l, &o)| (l, o)).collect()
+        };
+
+        let mut app = open();
+        app.move_byte_cursor(8);
+        app.set_mark('a');
+        app.enter_overwrite_mode(true);
+        app.write_bytes(0, &[1, 2, 3, 4]);
+        app.move_byte_cursor(2);
+        app.set_mark('b');
+        assert_eq!(marks(&app), vec![('a', 12), ('b', 2)]);
+        app.input = "q!".to_string();
+        app.run_command();
+        assert_eq!(marks(&open()), vec![('a', 8)]);
+
+        let mut app = open();
+        app.enter_overwrite_mode(true);
+        app.write_bytes(0, &[1, 2, 3, 4]);
+        app.set_mark('b');
+        app.input = "w".to_string();
+        app.run_command();
+        assert_eq!(marks(&open()), vec![('a', 12), ('b', 4)]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_typing_spaces_in_text_formats() {
+        let mut app = app(b"!");
+        app.current_format = Format::Ascii;
+        app.enter_overwrite_mode(true);
+        "a b".chars().for_each(|c| app.overwrite_char(c));
+        assert_eq!(&*app.buffer.read(0, 8), b"a b!");
+        app.enter_overwrite_mode(false);
+        app.move_byte_cursor(0);
+        app.overwrite_char(' ');
+        assert_eq!(&*app.buffer.read(0, 8), b"  b!");
+    }
+
+    #[test]
+    fn test_template_records_longer_than_the_window() {
+        let mut bytes = vec![b'x'; TEMPLATE_WINDOW + 0x10];
+        bytes.push(7);
+        let mut app = app(&bytes);
+        let template = format!(
+            "struct A {{\n  data: char[{}]\n  tail: u8\n}}",
+            bytes.len() - 1
+        );
+        app.template = Some(Template::parse(&template).unwrap());
+        app.refresh_template_record();
+        let record = app.template_record.as_ref().unwrap();
+        assert_eq!(record.result.as_ref().unwrap().children[1].value, "7");
+
+        app.template = Some(Template::parse("struct A {\n  data: char[131072]\n}").unwrap());
+        app.template_record = None;
+        app.refresh_template_record();
+        let record = app.template_record.as_ref().unwrap();
+        assert_eq!(
+            record.result,
+            Err(TemplateError::OutOfBounds("data".to_string(), 0))
+        );
+    }
+
+    #[test]
+    fn test_panels_are_computed_once_for_several_edits() {
+        let mut app = app(&[0; 16]);
+        app.toggle_strings_panel();
+        app.enter_overwrite_mode(true);
+        app.write_bytes(0, b"byte");
+        app.write_bytes(4, b"blitz");
+        assert!(app.panels_stale);
+        assert!(app.strings_panel.as_ref().unwrap().strings.is_empty());
+
+        app.refresh_panels();
+        assert!(!app.panels_stale);
+        let strings = &app.strings_panel.as_ref().unwrap().strings;
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[0].text, "byteblitz");
+    }
 }
@@ -0,0 +1,356 @@
+//! Marks and named bookmarks on byte offsets of a file. They are kept in a
+//! TOML file per viewed file, so that they are still there when the file is
+//! opened again:
+//!
+//! ```toml
+//! file = "/home/user/firmware.bin"
+//!
+//! [marks]
+//! a = 4096
+//!
+//! [[bookmarks]]
+//! name = "header"
+//! offset = 0
+//! comment = "version 2 layout"
+//! ```
+//!
+//! Only the part of TOML that is written here is read back.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::conversion_utils::escape::quoted;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BookmarksError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("neither $XDG_DATA_HOME nor $HOME is set")]
+    NoDataHome,
+    #[error("{path} can't be resolved: {message}")]
+    Unresolved { path: String, message: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bookmark {
+    pub name: String,
+    pub offset: usize,
+    pub comment: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Bookmarks {
+    /// Offsets of the marks set with `m` and a letter.
+    pub marks: BTreeMap<char, usize>,
+    /// Named bookmarks, ordered by their offset.
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// One line of the bookmarks panel.
+#[derive(Debug, PartialEq)]
+pub struct Entry<'a> {
+    pub label: String,
+    pub offset: usize,
+    pub comment: &'a str,
+}
+
+enum Value {
+    Integer(usize),
+    String(String),
+}
+
+fn syntax(line: usize, message: impl Into<String>) -> BookmarksError {
+    BookmarksError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+/// Parses a basic string, TOML uses the same escapes as JSON.
+fn parse_string(text: &str, line: usize) -> Result<String, BookmarksError> {
+    let mut chars = text.chars();
+    let mut parsed = String::new();
+    loop {
+        match chars.next() {
+            None => return Err(syntax(line, "unterminated string")),
+            Some('"') => break,
+            Some('\\') => {
+                let escaped = match chars.next() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => {
+                        let digits: String = chars.by_ref().take(4).collect();
+                        u32::from_str_radix(&digits, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| syntax(line, format!("invalid escape '\\u{digits}'")))?
+                    }
+                    other => {
+                        return Err(syntax(
+                            line,
+                            format!("invalid escape '\\{}'", other.unwrap_or_default()),
+                        ))
+                    }
+                };
+                parsed.push(escaped);
+            }
+            Some(c) => parsed.push(c),
+        }
+    }
+    if !chars.as_str().trim().is_empty() {
+        return Err(syntax(line, "unexpected text after the string"));
+    }
+    Ok(parsed)
+}
+
+fn parse_value(text: &str, line: usize) -> Result<Value, BookmarksError> {
+    match text.strip_prefix('"') {
+        Some(string) => parse_string(string, line).map(Value::String),
+        None => text
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| syntax(line, format!("'{text}' is neither a string nor an offset"))),
+    }
+}
+
+impl Bookmarks {
+    pub fn parse(source: &str) -> Result<Bookmarks, BookmarksError> {
+        let mut parsed = Bookmarks::default();
+        let mut in_marks = false;
+        for (index, line) in source.lines().enumerate() {
+            let number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "[marks]" {
+                in_marks = true;
+                continue;
+            }
+            if line == "[[bookmarks]]" {
+                in_marks = false;
+                parsed.bookmarks.push(Bookmark::default());
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(syntax(
+                    number,
+                    format!("expected 'key = value', got '{line}'"),
+                ));
+            };
+            let key = key.trim();
+            let value = parse_value(value.trim(), number)?;
+            if in_marks {
+                let mut letters = key.chars();
+                match (letters.next(), letters.next(), value) {
+                    (Some(letter), None, Value::Integer(offset))
+                        if letter.is_ascii_alphabetic() =>
+                    {
+                        parsed.marks.insert(letter, offset);
+                    }
+                    _ => return Err(syntax(number, format!("'{key}' isn't a mark letter"))),
+                }
+                continue;
+            }
+            let Some(bookmark) = parsed.bookmarks.last_mut() else {
+                // The path of the file is only written for people reading along
+                continue;
+            };
+            match (key, value) {
+                ("name", Value::String(name)) => bookmark.name = name,
+                ("offset", Value::Integer(offset)) => bookmark.offset = offset,
+                ("comment", Value::String(comment)) => bookmark.comment = comment,
+                _ => return Err(syntax(number, format!("unexpected key '{key}'"))),
+            }
+        }
+        parsed.bookmarks.sort_by_key(|bookmark| bookmark.offset);
+        Ok(parsed)
+    }
+
+    /// Writes the bookmarks of `file` in the format [Bookmarks::parse] reads.
+    pub fn to_toml(&self, file: &str) -> String {
+        let mut toml = format!("file = {}\n", quoted(file));
+        if !self.marks.is_empty() {
+            toml.push_str("\n[marks]\n");
+            for (letter, offset) in &self.marks {
+                toml.push_str(&format!("{letter} = {offset}\n"));
+            }
+        }
+        for bookmark in &self.bookmarks {
+            toml.push_str(&format!(
+                "\n[[bookmarks]]\nname = {}\noffset = {}\ncomment = {}\n",
+                quoted(&bookmark.name),
+                bookmark.offset,
+                quoted(&bookmark.comment)
+            ));
+        }
+        toml
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty() && self.bookmarks.is_empty()
+    }
+
+    /// Adds a bookmark, or moves the one with the same name.
+    pub fn add(&mut self, bookmark: Bookmark) {
+        self.bookmarks
+            .retain(|existing| existing.name != bookmark.name);
+        let index = self
+            .bookmarks
+            .partition_point(|existing| existing.offset <= bookmark.offset);
+        self.bookmarks.insert(index, bookmark);
+    }
+
+    /// The marks followed by the bookmarks, the way the panel lists them.
+    pub fn entries(&self) -> Vec<Entry<'_>> {
+        let marks = self.marks.iter().map(|(letter, &offset)| Entry {
+            label: format!("'{letter}"),
+            offset,
+            comment: "",
+        });
+        let bookmarks = self.bookmarks.iter().map(|bookmark| Entry {
+            label: bookmark.name.clone(),
+            offset: bookmark.offset,
+            comment: &bookmark.comment,
+        });
+        marks.chain(bookmarks).collect()
+    }
+
+    /// Moves the marks and bookmarks along with their bytes when `removed`
+    /// bytes at `offset` are replaced by `inserted` ones. Those on removed
+    /// bytes end up at `offset`.
+    pub fn shift(&mut self, offset: usize, removed: usize, inserted: usize) {
+        let shift = |position: &mut usize| {
+            if *position >= offset + removed {
+                *position = *position - removed + inserted;
+            } else if *position >= offset {
+                *position = offset;
+            }
+        };
+        self.marks.values_mut().for_each(shift);
+        self.bookmarks
+            .iter_mut()
+            .for_each(|bookmark| shift(&mut bookmark.offset));
+    }
+
+    /// Removes the mark or bookmark at `index` of [Bookmarks::entries].
+    pub fn remove(&mut self, index: usize) {
+        match self.marks.keys().nth(index).copied() {
+            Some(letter) => {
+                self.marks.remove(&letter);
+            }
+            None if index - self.marks.len() < self.bookmarks.len() => {
+                self.bookmarks.remove(index - self.marks.len());
+            }
+            None => {}
+        }
+    }
+}
+
+/// FNV-1a, which names the bookmarks file of a path.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// Where the bookmarks of `file` are kept: `$XDG_DATA_HOME/byteblitz`, or
+/// `~/.local/share/byteblitz` if it isn't set, in a file named after the
+/// hash of the absolute path.
+pub fn bookmarks_path(file: &Path) -> Result<PathBuf, BookmarksError> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .ok_or(BookmarksError::NoDataHome)?;
+    let file = file
+        .canonicalize()
+        .map_err(|e| BookmarksError::Unresolved {
+            path: file.display().to_string(),
+            message: e.to_string(),
+        })?;
+    let hash = fnv1a(file.as_os_str().as_encoded_bytes());
+    Ok(data_home
+        .join("byteblitz")
+        .join(format!("{hash:016x}.toml")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.marks.insert('a', 4096);
+        bookmarks.marks.insert('B', 16);
+        bookmarks.add(Bookmark {
+            name: "data".to_string(),
+            offset: 0x200,
+            comment: "say \"hi\"\n\tthere".to_string(),
+        });
+        bookmarks.add(Bookmark {
+            name: "header".to_string(),
+            offset: 0,
+            comment: String::new(),
+        });
+
+        let toml = bookmarks.to_toml("/tmp/a \"b\".bin");
+        assert!(toml.starts_with("file = \"/tmp/a \\\"b\\\".bin\"\n\n[marks]\nB = 16\na = 4096\n"));
+        assert_eq!(Bookmarks::parse(&toml), Ok(bookmarks));
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut bookmarks = Bookmarks::parse(
+            "[marks]\na = 8\n\n[[bookmarks]]\nname = \"x\"\noffset = 4\ncomment = \"\\u00e9\"\n",
+        )
+        .unwrap();
+        let entries: Vec<(String, usize, &str)> = bookmarks
+            .entries()
+            .into_iter()
+            .map(|entry| (entry.label, entry.offset, entry.comment))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![("'a".to_string(), 8, ""), ("x".to_string(), 4, "é")]
+        );
+
+        bookmarks.remove(0);
+        assert!(bookmarks.marks.is_empty());
+        bookmarks.remove(0);
+        assert!(bookmarks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Bookmarks::parse("[marks]\nab = 1"),
+            Err(syntax(2, "'ab' isn't a mark letter"))
+        );
+        assert_eq!(
+            Bookmarks::parse("[[bookmarks]]\nname = \"x"),
+            Err(syntax(2, "unterminated string"))
+        );
+        assert_eq!(
+            Bookmarks::parse("[[bookmarks]]\nsize = 1"),
+            Err(syntax(2, "unexpected key 'size'"))
+        );
+    }
+
+    #[test]
+    fn test_missing_files_have_no_bookmarks_path() {
+        let path = bookmarks_path(Path::new("/nonexistent/byteblitz.bin"));
+        assert!(matches!(
+            path,
+            Err(BookmarksError::Unresolved { .. } | BookmarksError::NoDataHome)
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use memmap2::{Mmap, MmapOptions};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Read-only view of the bytes that are being examined.
+///
+/// Files are memory mapped so that opening them is instant regardless of
+/// their size, and pages are only pulled in by the OS once a part of the file
+/// is actually displayed.
+pub enum ByteSource {
+    Mapped(Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl ByteSource {
+    /// Opens the file at `path`, or reads stdin if `path` is `-`. If `limit`
+    /// is given, only the first `limit` bytes of the file are mapped.
+    pub fn open(path: impl AsRef<Path>, limit: Option<usize>) -> io::Result<ByteSource> {
+        if path.as_ref() == Path::new("-") {
+            return ByteSource::from_reader(io::stdin().lock(), limit);
+        }
+        let file = File::open(path)?;
+        let file_len = usize::try_from(file.metadata()?.len()).unwrap_or(usize::MAX);
+        let len = limit.map_or(file_len, |limit| limit.min(file_len));
+        // Mapping an empty file fails on some platforms
+        if len == 0 {
+            return Ok(ByteSource::Buffered(Vec::new()));
+        }
+        // Safety: the map is read-only. Modifying the file from another
+        // process while it is open is undefined behaviour, which is the same
+        // trade-off every mmap based viewer makes.
+        let map = unsafe { MmapOptions::new().len(len).map(&file)? };
+        Ok(ByteSource::Mapped(map))
+    }
+
+    /// Reads `reader` to its end, or up to `limit` bytes. Pipes can't be
+    /// mapped, so their contents are kept in memory.
+    pub fn from_reader(reader: impl Read, limit: Option<usize>) -> io::Result<ByteSource> {
+        let limit = limit.map_or(u64::MAX, |limit| limit as u64);
+        let mut bytes = Vec::new();
+        reader.take(limit).read_to_end(&mut bytes)?;
+        Ok(ByteSource::Buffered(bytes))
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            ByteSource::Mapped(map) => map,
+            ByteSource::Buffered(bytes) => bytes,
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Returns at most `len` bytes starting at `offset`. The returned slice is
+    /// shorter when the range reaches past the end of the source.
+    #[cfg(feature = "tui")]
+    pub fn read(&self, offset: usize, len: usize) -> &[u8] {
+        let bytes = self.as_slice();
+        let start = offset.min(bytes.len());
+        let end = offset.saturating_add(len).min(bytes.len());
+        &bytes[start..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "tui")]
+    fn test_read_is_clamped_to_the_source() {
+        let source = ByteSource::Buffered(vec![1, 2, 3, 4, 5]);
+        assert_eq!(source.read(1, 2), &[2, 3]);
+        assert_eq!(source.read(3, 10), &[4, 5]);
+        assert_eq!(source.read(10, 2), &[] as &[u8]);
+    }
+
+    #[test]
+    fn test_from_reader_stops_at_the_limit() {
+        let source = ByteSource::from_reader(&[1, 2, 3, 4, 5][..], Some(3)).unwrap();
+        assert_eq!(source.as_slice(), &[1, 2, 3]);
+        let source = ByteSource::from_reader(&[1, 2][..], None).unwrap();
+        assert_eq!(source.as_slice(), &[1, 2]);
+    }
+}
@@ -0,0 +1,116 @@
+//! Text that bytes are copied to the clipboard as. The clipboard is set with
+//! the OSC 52 escape sequence, which the terminal handles, so copying works
+//! over SSH as well.
+
+use crate::{
+    decoder::Decoder,
+    enums::{endian::Endian, format::Format},
+};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// How copied bytes are written out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Representation {
+    /// `DEADBEEF`
+    Hex,
+    /// `{0xDE, 0xAD, 0xBE, 0xEF}`
+    CArray,
+    /// `[0xDE, 0xAD, 0xBE, 0xEF]`, a `[u8; 4]`
+    RustArray,
+    Base64,
+    /// The values of a format, one per line.
+    Values(Format, Endian),
+}
+
+impl Representation {
+    pub fn name(&self) -> String {
+        match self {
+            Representation::Hex => "hex".to_string(),
+            Representation::CArray => "C array".to_string(),
+            Representation::RustArray => "Rust array".to_string(),
+            Representation::Base64 => "base64".to_string(),
+            Representation::Values(format, _) => format!("{format:?} values"),
+        }
+    }
+
+    pub fn format(&self, bytes: &[u8]) -> String {
+        let elements = || {
+            bytes
+                .iter()
+                .map(|byte| format!("{byte:#04X}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Representation::Hex => bytes.iter().map(|byte| format!("{byte:02X}")).collect(),
+            Representation::CArray => format!("{{{}}}", elements()),
+            Representation::RustArray => format!("[{}]", elements()),
+            Representation::Base64 => base64(bytes),
+            Representation::Values(format, endian) => Decoder::new(bytes, *format, *endian, 0)
+                .map(|value| value.text)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Standard base64 with padding.
+pub fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (index, &byte)| {
+                group | u32::from(byte) << (16 - 8 * index)
+            });
+        // Every byte of the chunk spreads over one more character
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3F;
+                encoded.push(char::from(BASE64_ALPHABET[sextet as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Escape sequence that asks the terminal to put `text` on the clipboard.
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_representations() {
+        let bytes = [0xDE, 0xAD, 0xBE, 0xEF];
+        assert_eq!(Representation::Hex.format(&bytes), "DEADBEEF");
+        assert_eq!(
+            Representation::CArray.format(&bytes),
+            "{0xDE, 0xAD, 0xBE, 0xEF}"
+        );
+        assert_eq!(Representation::RustArray.format(&bytes[..1]), "[0xDE]");
+        assert_eq!(
+            Representation::Values(Format::Uint16, Endian::Little).format(&bytes),
+            "44510\n61374"
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xFF, 0xEF]), "/+8=");
+        assert_eq!(osc52("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
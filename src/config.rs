@@ -1,25 +1,334 @@
 use std::env;
+use thiserror::Error;
+
+use crate::{
+    dump::Output,
+    enums::{endian::Endian, format::Format},
+    navigation::parse_offset,
+};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ConfigError {
+    #[error("user needs help")]
+    HelpRequested,
+    #[error("not enough arguments")]
+    NotEnoughArguments,
+    #[error("unknown flag '{0}'")]
+    UnknownFlag(String),
+    #[error("unexpected argument '{0}'")]
+    UnexpectedArgument(String),
+    #[error("flag '{0}' requires a value")]
+    MissingValue(String),
+    #[error("invalid value '{value}' for flag '{flag}'")]
+    InvalidValue { flag: String, value: String },
+}
 
 pub struct Config {
     pub file_path: String,
     pub little_endianess: bool,
+    /// Byte offset the view starts at.
+    pub offset: usize,
+    /// Number of bytes after `offset` that are shown, the rest of the file if
+    /// [None].
+    pub length: Option<usize>,
+    /// Format that is displayed when the application starts.
+    pub format: Format,
+    /// File that `file_path` is compared against in diff mode.
+    pub diff_path: Option<String>,
+    /// Template describing the records of the file.
+    pub template_path: Option<String>,
 }
+
 impl Config {
-    pub fn new(args: &[String]) -> Result<Config, &'static str> {
+    pub fn new(args: &[String]) -> Result<Config, ConfigError> {
         if args.contains(&"-h".to_string()) || args.contains(&"--help".to_string()) {
-            return Err("user needs help");
+            return Err(ConfigError::HelpRequested);
         }
 
-        if args.len() < 2 {
-            return Err("not enough arguments");
-        }
+        let mut file_path = None;
+        let mut little_endianess = env::var("LITTLE_ENDIAN").is_ok();
+        let mut offset = 0;
+        let mut length = None;
+        let mut format = Format::Hex;
+        let mut diff = false;
+        let mut diff_path = None;
+        let mut template_path = None;
 
-        let little_endianess = env::var("LITTLE_ENDIAN").is_ok();
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            // A lone `-` stands for stdin
+            if !arg.starts_with('-') || arg == "-" {
+                if file_path.is_none() {
+                    file_path = Some(arg.clone());
+                } else if diff_path.is_none() {
+                    diff_path = Some(arg.clone());
+                } else {
+                    return Err(ConfigError::UnexpectedArgument(arg.clone()));
+                }
+                continue;
+            }
 
-        let file_path = args[1].clone();
+            // Both `--flag value` and `--flag=value` are accepted
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| ConfigError::MissingValue(flag.to_string()))
+            };
+            let invalid = |value: String| ConfigError::InvalidValue {
+                flag: flag.to_string(),
+                value,
+            };
+
+            match flag {
+                "-l" | "--little" => little_endianess = true,
+                "-b" | "--big" => little_endianess = false,
+                "-d" | "--diff" => diff = true,
+                "-o" | "--offset" => {
+                    let value = value()?;
+                    offset = parse_offset(&value).ok_or_else(|| invalid(value))?;
+                }
+                "-n" | "--length" => {
+                    let value = value()?;
+                    length = Some(parse_offset(&value).ok_or_else(|| invalid(value))?);
+                }
+                "-t" | "--template" => template_path = Some(value()?),
+                "-f" | "--format" => {
+                    let value = value()?;
+                    format = value.parse().map_err(|_| invalid(value))?;
+                }
+                _ => return Err(ConfigError::UnknownFlag(arg.clone())),
+            }
+        }
+
+        let file_path = file_path.ok_or(ConfigError::NotEnoughArguments)?;
+        // A second file only makes sense when comparing
+        match (diff, &diff_path) {
+            (true, None) => return Err(ConfigError::NotEnoughArguments),
+            (false, Some(path)) => return Err(ConfigError::UnexpectedArgument(path.clone())),
+            _ => {}
+        }
         Ok(Config {
             file_path,
             little_endianess,
+            offset,
+            length,
+            format,
+            diff_path,
+            template_path,
         })
     }
 }
+
+/// Options of `byteblitz dump`, which prints values without starting the
+/// interface.
+pub struct DumpConfig {
+    pub file_path: String,
+    pub format: Format,
+    pub endian: Endian,
+    pub offset: usize,
+    /// Number of values that are printed, every value up to the end of the
+    /// file if [None].
+    pub count: Option<usize>,
+    pub output: Output,
+}
+
+impl DumpConfig {
+    /// Parses the arguments following `byteblitz dump`.
+    pub fn new(args: &[String]) -> Result<DumpConfig, ConfigError> {
+        if args.contains(&"-h".to_string()) || args.contains(&"--help".to_string()) {
+            return Err(ConfigError::HelpRequested);
+        }
+
+        let mut file_path = None;
+        let mut format = Format::Hex;
+        let mut endian = if env::var("LITTLE_ENDIAN").is_ok() {
+            Endian::Little
+        } else {
+            Endian::Big
+        };
+        let mut offset = 0;
+        let mut count = None;
+        let mut output = Output::Plain;
+
+        let mut args = args.iter().skip(2);
+        while let Some(arg) = args.next() {
+            // A lone `-` stands for stdin
+            if !arg.starts_with('-') || arg == "-" {
+                if file_path.is_some() {
+                    return Err(ConfigError::UnexpectedArgument(arg.clone()));
+                }
+                file_path = Some(arg.clone());
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| ConfigError::MissingValue(flag.to_string()))
+            };
+            let invalid = |value: String| ConfigError::InvalidValue {
+                flag: flag.to_string(),
+                value,
+            };
+
+            match flag {
+                "-l" | "--little" => endian = Endian::Little,
+                "-b" | "--big" => endian = Endian::Big,
+                "-e" | "--endian" => {
+                    let value = value()?;
+                    endian = match value.to_ascii_lowercase().as_str() {
+                        "little" | "le" => Endian::Little,
+                        "big" | "be" => Endian::Big,
+                        _ => return Err(invalid(value)),
+                    };
+                }
+                "-o" | "--offset" => {
+                    let value = value()?;
+                    offset = parse_offset(&value).ok_or_else(|| invalid(value))?;
+                }
+                "-c" | "--count" => {
+                    let value = value()?;
+                    count = Some(parse_offset(&value).ok_or_else(|| invalid(value))?);
+                }
+                "-f" | "--format" => {
+                    let value = value()?;
+                    format = value.parse().map_err(|_| invalid(value))?;
+                }
+                "--output" => {
+                    let value = value()?;
+                    output = value.parse().map_err(|_| invalid(value))?;
+                }
+                _ => return Err(ConfigError::UnknownFlag(arg.clone())),
+            }
+        }
+
+        let file_path = file_path.ok_or(ConfigError::NotEnoughArguments)?;
+        Ok(DumpConfig {
+            file_path,
+            format,
+            endian,
+            offset,
+            count,
+            output,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        std::iter::once("byteblitz")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let config = Config::new(&args(&[
+            "--little",
+            "file.bin",
+            "--offset",
+            "0x1F40",
+            "--length=64",
+            "-f",
+            "u32",
+            "-t",
+            "header.tpl",
+        ]))
+        .unwrap();
+        assert_eq!(config.file_path, "file.bin");
+        assert!(config.little_endianess);
+        assert_eq!(config.offset, 0x1F40);
+        assert_eq!(config.length, Some(64));
+        assert_eq!(config.format, Format::Uint32);
+        assert_eq!(config.template_path.as_deref(), Some("header.tpl"));
+
+        let config = Config::new(&args(&["file.bin", "--big", "--format", "f64"])).unwrap();
+        assert!(!config.little_endianess);
+        assert_eq!(config.format, Format::F64);
+        assert_eq!(config.diff_path, None);
+
+        let config = Config::new(&args(&["--diff", "a.bin", "b.bin"])).unwrap();
+        assert_eq!(config.file_path, "a.bin");
+        assert_eq!(config.diff_path.as_deref(), Some("b.bin"));
+
+        let config = Config::new(&args(&["-", "-l"])).unwrap();
+        assert_eq!(config.file_path, "-");
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert_eq!(
+            Config::new(&args(&["file.bin", "--verbose"])).err(),
+            Some(ConfigError::UnknownFlag("--verbose".to_string()))
+        );
+        assert_eq!(
+            Config::new(&args(&["file.bin", "--offset"])).err(),
+            Some(ConfigError::MissingValue("--offset".to_string()))
+        );
+        assert_eq!(
+            Config::new(&args(&["file.bin", "--format", "u128"])).err(),
+            Some(ConfigError::InvalidValue {
+                flag: "--format".to_string(),
+                value: "u128".to_string()
+            })
+        );
+        assert_eq!(
+            Config::new(&args(&["a.bin", "b.bin"])).err(),
+            Some(ConfigError::UnexpectedArgument("b.bin".to_string()))
+        );
+        assert_eq!(
+            Config::new(&args(&[])).err(),
+            Some(ConfigError::NotEnoughArguments)
+        );
+        assert_eq!(
+            Config::new(&args(&["--diff", "a.bin"])).err(),
+            Some(ConfigError::NotEnoughArguments)
+        );
+    }
+
+    #[test]
+    fn test_parse_dump_flags() {
+        let config = DumpConfig::new(&args(&[
+            "dump",
+            "--format",
+            "u32",
+            "--endian",
+            "little",
+            "--offset",
+            "0x100",
+            "--count",
+            "64",
+            "--output=json",
+            "file.bin",
+        ]))
+        .unwrap();
+        assert_eq!(config.file_path, "file.bin");
+        assert_eq!(config.format, Format::Uint32);
+        assert_eq!(config.endian, Endian::Little);
+        assert_eq!(config.offset, 0x100);
+        assert_eq!(config.count, Some(64));
+        assert_eq!(config.output, Output::Json);
+
+        assert_eq!(
+            DumpConfig::new(&args(&["dump", "file.bin", "--endian", "middle"])).err(),
+            Some(ConfigError::InvalidValue {
+                flag: "--endian".to_string(),
+                value: "middle".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! ELF executables and libraries: the file header, the program headers and
+//! the sections.
+
+use super::{node, read_uint};
+use crate::{enums::endian::Endian, template::Node};
+
+pub const MAGIC: &[u8] = b"\x7FELF";
+
+/// Offsets and sizes that differ between 32 and 64-bit files.
+struct Layout {
+    endian: Endian,
+    address: usize,
+}
+
+impl Layout {
+    /// Offset of a header field given its offsets in 32 and 64-bit files.
+    fn pick(&self, offset_32: usize, offset_64: usize) -> usize {
+        match self.address {
+            4 => offset_32,
+            _ => offset_64,
+        }
+    }
+}
+
+fn file_type(value: u64) -> &'static str {
+    match value {
+        1 => "relocatable",
+        2 => "executable",
+        3 => "shared object",
+        4 => "core",
+        _ => "",
+    }
+}
+
+fn section_type(value: u64) -> String {
+    match value {
+        0 => "NULL".to_string(),
+        1 => "PROGBITS".to_string(),
+        2 => "SYMTAB".to_string(),
+        3 => "STRTAB".to_string(),
+        4 => "RELA".to_string(),
+        5 => "HASH".to_string(),
+        6 => "DYNAMIC".to_string(),
+        7 => "NOTE".to_string(),
+        8 => "NOBITS".to_string(),
+        9 => "REL".to_string(),
+        11 => "DYNSYM".to_string(),
+        other => format!("type {other:#X}"),
+    }
+}
+
+fn segment_type(value: u64) -> String {
+    match value {
+        0 => "NULL".to_string(),
+        1 => "LOAD".to_string(),
+        2 => "DYNAMIC".to_string(),
+        3 => "INTERP".to_string(),
+        4 => "NOTE".to_string(),
+        6 => "PHDR".to_string(),
+        7 => "TLS".to_string(),
+        other => format!("type {other:#X}"),
+    }
+}
+
+/// Null terminated string at `offset`.
+fn c_string(bytes: &[u8], offset: usize) -> String {
+    let tail = bytes.get(offset..).unwrap_or_default();
+    let end = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+    String::from_utf8_lossy(&tail[..end]).into_owned()
+}
+
+pub fn parse(bytes: &[u8]) -> Node {
+    let mut root = node("ELF", 0, bytes.len(), "");
+    let (Some(class), Some(data)) = (bytes.get(4), bytes.get(5)) else {
+        root.value = "truncated".to_string();
+        return root;
+    };
+    let layout = Layout {
+        endian: if *data == 2 {
+            Endian::Big
+        } else {
+            Endian::Little
+        },
+        address: if *class == 2 { 8 } else { 4 },
+    };
+    root.value = format!("{}-bit, {:?} endian", layout.address * 8, layout.endian);
+    let read = |offset: usize, size: usize| read_uint(bytes, offset, size, layout.endian);
+
+    let header_fields = [
+        ("type", 16, 16, 2),
+        ("machine", 18, 18, 2),
+        ("version", 20, 20, 4),
+        ("entry point", 24, 24, layout.address),
+        ("program header offset", 28, 32, layout.address),
+        ("section header offset", 32, 40, layout.address),
+        ("flags", 36, 48, 4),
+        ("header size", 40, 52, 2),
+        ("program header size", 42, 54, 2),
+        ("program header count", 44, 56, 2),
+        ("section header size", 46, 58, 2),
+        ("section header count", 48, 60, 2),
+        ("section name table index", 50, 62, 2),
+    ];
+    let mut header = node("file header", 0, layout.pick(52, 64), "");
+    for (name, offset_32, offset_64, size) in header_fields {
+        let offset = layout.pick(offset_32, offset_64);
+        if let Some(value) = read(offset, size) {
+            let value = match name {
+                "type" => format!("{value} {}", file_type(value)),
+                "entry point" | "program header offset" | "section header offset" => {
+                    format!("{value:#X}")
+                }
+                _ => value.to_string(),
+            };
+            header
+                .children
+                .push(node(name, offset, size, value.trim_end()));
+        }
+    }
+    root.children.push(header);
+
+    let field = |offset_32: usize, offset_64: usize, size: usize| {
+        read(layout.pick(offset_32, offset_64), size).unwrap_or_default() as usize
+    };
+    let address = layout.address;
+
+    // Segments, as the program headers describe them
+    let (offset, entry_size, count) = (field(28, 32, address), field(42, 54, 2), field(44, 56, 2));
+    let mut segments = node("program headers", offset, entry_size * count, "");
+    for index in 0..count {
+        let entry = offset.saturating_add(index * entry_size);
+        let (Some(kind), Some(start), Some(size)) = (
+            read(entry, 4),
+            read(entry.saturating_add(layout.pick(4, 8)), address),
+            read(entry.saturating_add(layout.pick(16, 32)), address),
+        ) else {
+            break;
+        };
+        segments.children.push(node(
+            format!("{index}: {}", segment_type(kind)),
+            start as usize,
+            size as usize,
+            format!("{size} bytes at {start:#X}"),
+        ));
+    }
+    root.children.push(segments);
+
+    // Sections, named after the strings in the section name table
+    let (offset, entry_size, count) = (field(32, 40, address), field(46, 58, 2), field(48, 60, 2));
+    let names_entry = offset.saturating_add(field(50, 62, 2) * entry_size);
+    let names = read(names_entry.saturating_add(layout.pick(16, 24)), address).unwrap_or_default();
+    let mut sections = node("section headers", offset, entry_size * count, "");
+    for index in 0..count {
+        let entry = offset.saturating_add(index * entry_size);
+        let (Some(name), Some(kind), Some(start), Some(size)) = (
+            read(entry, 4),
+            read(entry.saturating_add(4), 4),
+            read(entry.saturating_add(layout.pick(16, 24)), address),
+            read(entry.saturating_add(layout.pick(20, 32)), address),
+        ) else {
+            break;
+        };
+        let name = c_string(bytes, names.saturating_add(name) as usize);
+        // These sections take up no space in the file
+        let size_in_file = if kind == 8 { 0 } else { size as usize };
+        sections.children.push(node(
+            format!("{index}: {name}"),
+            start as usize,
+            size_in_file,
+            format!("{}, {size} bytes at {start:#X}", section_type(kind)),
+        ));
+    }
+    root.children.push(sections);
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sections() {
+        // A 64-bit little endian header with two sections: the null section
+        // and the section name table
+        let mut bytes = vec![0u8; 64];
+        bytes[..6].copy_from_slice(b"\x7FELF\x02\x01");
+        bytes[16] = 2; // executable
+        bytes[24] = 0x40; // entry point
+        bytes[40] = 80; // section header offset
+        bytes[58] = 64; // section header size
+        bytes[60] = 2; // section header count
+        bytes[62] = 1; // section name table index
+        bytes.extend(b"\0.shstrtab\0\0\0\0\0\0");
+        bytes.extend([0; 64]);
+        let mut names = [0u8; 64];
+        names[0] = 1;
+        names[4] = 3;
+        names[24] = 64;
+        names[32] = 11;
+        bytes.extend(names);
+
+        let root = parse(&bytes);
+        assert_eq!(root.value, "64-bit, Little endian");
+        let header = &root.children[0];
+        assert_eq!(header.children[0].value, "2 executable");
+        assert_eq!(header.children[3].value, "0x40");
+
+        let sections = &root.children[2].children;
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1].name, "1: .shstrtab");
+        assert_eq!((sections[1].offset, sections[1].size), (64, 11));
+        assert_eq!(sections[1].value, "STRTAB, 11 bytes at 0x40");
+    }
+}
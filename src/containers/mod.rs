@@ -0,0 +1,73 @@
+//! Parsers for well known file formats. They recognize a file by its magic
+//! bytes and lay out its structure as a tree of [Node]s, the same way records
+//! decoded by a template are shown.
+//!
+//! The parsers are lenient: a damaged or truncated file is parsed as far as
+//! possible instead of being rejected.
+
+pub mod elf;
+pub mod png;
+pub mod wav;
+pub mod zip;
+
+use crate::{enums::endian::Endian, template::Node};
+
+/// Parses `bytes` with the parser whose magic bytes they start with.
+pub fn parse(bytes: &[u8]) -> Option<Node> {
+    if bytes.starts_with(elf::MAGIC) {
+        Some(elf::parse(bytes))
+    } else if bytes.starts_with(png::MAGIC) {
+        Some(png::parse(bytes))
+    } else if bytes.starts_with(zip::LOCAL_HEADER) || bytes.starts_with(zip::END_OF_DIRECTORY) {
+        Some(zip::parse(bytes))
+    } else if bytes.starts_with(wav::MAGIC) {
+        Some(wav::parse(bytes))
+    } else {
+        None
+    }
+}
+
+/// Reads an unsigned integer of `size` bytes at `offset`.
+fn read_uint(bytes: &[u8], offset: usize, size: usize, endian: Endian) -> Option<u64> {
+    let bytes = bytes.get(offset..offset.checked_add(size)?)?;
+    let push = |value: u64, byte: &u8| value << 8 | u64::from(*byte);
+    Some(match endian {
+        Endian::Big => bytes.iter().fold(0, push),
+        Endian::Little => bytes.iter().rev().fold(0, push),
+    })
+}
+
+fn node(name: impl Into<String>, offset: usize, size: usize, value: impl Into<String>) -> Node {
+    Node {
+        name: name.into(),
+        offset,
+        size,
+        value: value.into(),
+        children: Vec::new(),
+    }
+}
+
+/// Node for the integer at `offset`, [None] if the file ends before it.
+fn uint_node(bytes: &[u8], name: &str, offset: usize, size: usize, endian: Endian) -> Option<Node> {
+    read_uint(bytes, offset, size, endian).map(|value| node(name, offset, size, value.to_string()))
+}
+
+/// Characters of a four character code such as a PNG chunk type.
+fn fourcc(bytes: &[u8], offset: usize) -> Option<String> {
+    let code = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(code.iter().map(|&b| char::from(b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_uint() {
+        let bytes = [0x12, 0x34, 0x56];
+        assert_eq!(read_uint(&bytes, 0, 2, Endian::Big), Some(0x1234));
+        assert_eq!(read_uint(&bytes, 1, 2, Endian::Little), Some(0x5634));
+        assert_eq!(read_uint(&bytes, 2, 2, Endian::Big), None);
+        assert!(parse(b"plain text").is_none());
+    }
+}
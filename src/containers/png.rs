@@ -0,0 +1,124 @@
+//! PNG files: a signature followed by chunks that each carry a CRC.
+
+use super::{fourcc, node, read_uint, uint_node};
+use crate::{enums::endian::Endian, template::Node};
+
+pub const MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// CRC-32 as used by PNG (and ZIP).
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Fields of the header chunk, which describes the image.
+fn header_fields(bytes: &[u8], offset: usize) -> Vec<Node> {
+    [
+        ("width", 0, 4),
+        ("height", 4, 4),
+        ("bit depth", 8, 1),
+        ("color type", 9, 1),
+        ("compression", 10, 1),
+        ("filter", 11, 1),
+        ("interlace", 12, 1),
+    ]
+    .into_iter()
+    .filter_map(|(name, start, size)| uint_node(bytes, name, offset + start, size, Endian::Big))
+    .collect()
+}
+
+pub fn parse(bytes: &[u8]) -> Node {
+    let mut root = node("PNG image", 0, bytes.len(), "");
+    root.children
+        .push(node("signature", 0, MAGIC.len(), "valid"));
+
+    let mut offset = MAGIC.len();
+    while let (Some(len), Some(chunk_type)) = (
+        read_uint(bytes, offset, 4, Endian::Big),
+        fourcc(bytes, offset + 4),
+    ) {
+        let len = len as usize;
+        let data = offset + 8;
+        let Some(stored) = read_uint(bytes, data.saturating_add(len), 4, Endian::Big) else {
+            root.children
+                .push(node(chunk_type, offset, bytes.len() - offset, "truncated"));
+            break;
+        };
+
+        let computed = crc32(&bytes[offset + 4..data + len]);
+        let crc = if computed == stored as u32 {
+            "CRC ok".to_string()
+        } else {
+            format!("CRC mismatch, stored {stored:#010X} but computed {computed:#010X}")
+        };
+        let mut chunk = node(
+            chunk_type.as_str(),
+            offset,
+            len + 12,
+            format!("{len} bytes, {crc}"),
+        );
+        chunk.children = vec![
+            node("length", offset, 4, len.to_string()),
+            node("type", offset + 4, 4, chunk_type.as_str()),
+            node("data", data, len, ""),
+            node("CRC", data + len, 4, format!("{stored:#010X}")),
+        ];
+        if chunk_type == "IHDR" {
+            chunk.children[2].children = header_fields(bytes, data);
+        }
+        root.children.push(chunk);
+
+        offset = data + len + 4;
+        if chunk_type == "IEND" {
+            break;
+        }
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(data);
+        let crc = crc32(&chunk[4..]);
+        chunk.extend_from_slice(&crc.to_be_bytes());
+        chunk
+    }
+
+    #[test]
+    fn test_parse_chunks() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(chunk(b"IHDR", &[0, 0, 0, 2, 0, 0, 0, 3, 8, 6, 0, 0, 0]));
+        let mut damaged = chunk(b"tEXt", b"hi");
+        damaged[9] = b'!';
+        bytes.extend(damaged);
+        bytes.extend(chunk(b"IEND", &[]));
+
+        let root = parse(&bytes);
+        let chunks: Vec<(&str, usize, &str)> = root.children[1..]
+            .iter()
+            .map(|chunk| (chunk.name.as_str(), chunk.offset, chunk.value.as_str()))
+            .collect();
+        assert_eq!(chunks[0], ("IHDR", 8, "13 bytes, CRC ok"));
+        assert_eq!(chunks[1].1, 33);
+        assert!(chunks[1].2.starts_with("2 bytes, CRC mismatch"));
+        assert_eq!(chunks[2], ("IEND", 47, "0 bytes, CRC ok"));
+
+        let header = &root.children[1].children[2].children;
+        assert_eq!(header[1].value, "3");
+        assert_eq!(header[3].value, "6");
+    }
+}
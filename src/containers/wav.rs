@@ -0,0 +1,87 @@
+//! RIFF files such as WAV: a header followed by chunks padded to even sizes.
+
+use super::{fourcc, node, read_uint, uint_node};
+use crate::{enums::endian::Endian, template::Node};
+
+pub const MAGIC: &[u8] = b"RIFF";
+
+/// Fields of the chunk describing the audio format.
+fn format_fields(bytes: &[u8], offset: usize) -> Vec<Node> {
+    [
+        ("audio format", 0, 2),
+        ("channels", 2, 2),
+        ("sample rate", 4, 4),
+        ("byte rate", 8, 4),
+        ("block align", 12, 2),
+        ("bits per sample", 14, 2),
+    ]
+    .into_iter()
+    .filter_map(|(name, start, size)| uint_node(bytes, name, offset + start, size, Endian::Little))
+    .collect()
+}
+
+pub fn parse(bytes: &[u8]) -> Node {
+    let form = fourcc(bytes, 8).unwrap_or_default();
+    let mut root = node(format!("RIFF {form}"), 0, bytes.len(), "");
+    root.children
+        .extend(uint_node(bytes, "size", 4, 4, Endian::Little));
+
+    let mut offset = 12;
+    while let (Some(id), Some(len)) = (
+        fourcc(bytes, offset),
+        read_uint(bytes, offset + 4, 4, Endian::Little),
+    ) {
+        let data = offset + 8;
+        let len = len as usize;
+        let available = len.min(bytes.len() - data);
+        let value = if available < len {
+            format!("{len} bytes, truncated to {available}")
+        } else {
+            format!("{len} bytes")
+        };
+        let mut chunk = node(id.as_str(), offset, available + 8, value);
+        chunk.children = vec![
+            node("id", offset, 4, id.as_str()),
+            node("size", offset + 4, 4, len.to_string()),
+            node("data", data, available, ""),
+        ];
+        if id == "fmt " {
+            chunk.children[2].children = format_fields(bytes, data);
+        }
+        root.children.push(chunk);
+
+        // Chunks with an odd size are followed by a padding byte
+        offset = data.saturating_add(len).saturating_add(len % 2);
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chunks() {
+        let mut bytes = b"RIFF\x00\x00\x00\x00WAVEfmt \x10\x00\x00\x00".to_vec();
+        bytes.extend([1, 0, 2, 0, 0x44, 0xAC, 0, 0, 0x10, 0xB1, 2, 0, 4, 0, 16, 0]);
+        bytes.extend(b"LIST\x03\x00\x00\x00abc\x00data\x08\x00\x00\x00\x01\x02");
+
+        let root = parse(&bytes);
+        assert_eq!(root.name, "RIFF WAVE");
+        let chunks: Vec<(&str, usize, &str)> = root.children[1..]
+            .iter()
+            .map(|chunk| (chunk.name.as_str(), chunk.offset, chunk.value.as_str()))
+            .collect();
+        assert_eq!(
+            chunks,
+            vec![
+                ("fmt ", 12, "16 bytes"),
+                ("LIST", 36, "3 bytes"),
+                ("data", 48, "8 bytes, truncated to 2"),
+            ]
+        );
+        let format = &root.children[1].children[2].children;
+        assert_eq!(format[1].value, "2");
+        assert_eq!(format[2].value, "44100");
+    }
+}
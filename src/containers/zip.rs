@@ -0,0 +1,189 @@
+//! ZIP archives, read through the central directory at their end.
+
+use super::{node, read_uint, uint_node};
+use crate::{enums::endian::Endian, template::Node};
+
+pub const LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+pub const END_OF_DIRECTORY: &[u8] = b"PK\x05\x06";
+const DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+
+/// The end of central directory record is 22 bytes plus a comment of up to
+/// 65535 bytes.
+const MAX_END_SIZE: usize = 22 + 0xFFFF;
+
+fn read(bytes: &[u8], offset: usize, size: usize) -> Option<usize> {
+    read_uint(bytes, offset, size, Endian::Little).map(|value| value as usize)
+}
+
+fn field(bytes: &[u8], name: &str, offset: usize, size: usize) -> Option<Node> {
+    uint_node(bytes, name, offset, size, Endian::Little)
+}
+
+fn text(bytes: &[u8], offset: usize, len: usize) -> String {
+    let end = offset.saturating_add(len).min(bytes.len());
+    String::from_utf8_lossy(bytes.get(offset..end).unwrap_or_default()).into_owned()
+}
+
+fn method_name(method: usize) -> String {
+    match method {
+        0 => "stored".to_string(),
+        8 => "deflated".to_string(),
+        12 => "bzip2".to_string(),
+        14 => "LZMA".to_string(),
+        93 => "zstd".to_string(),
+        other => format!("method {other}"),
+    }
+}
+
+/// The local header of a file and the compressed data following it.
+fn local_file(bytes: &[u8], offset: usize, compressed: usize) -> Option<Vec<Node>> {
+    if bytes.get(offset..offset + 4)? != LOCAL_HEADER {
+        return None;
+    }
+    let name_len = read(bytes, offset + 26, 2)?;
+    let extra_len = read(bytes, offset + 28, 2)?;
+    let header_len = 30 + name_len + extra_len;
+    let mut header = node("local header", offset, header_len, "");
+    header.children = [
+        ("version needed", 4, 2),
+        ("flags", 6, 2),
+        ("compression", 8, 2),
+        ("CRC-32", 14, 4),
+        ("compressed size", 18, 4),
+        ("uncompressed size", 22, 4),
+    ]
+    .into_iter()
+    .filter_map(|(name, start, size)| field(bytes, name, offset + start, size))
+    .collect();
+    header.children.push(node(
+        "file name",
+        offset + 30,
+        name_len,
+        text(bytes, offset + 30, name_len),
+    ));
+
+    let data = offset + header_len;
+    let available = compressed.min(bytes.len().saturating_sub(data));
+    Some(vec![header, node("data", data, available, "")])
+}
+
+fn directory_entry(bytes: &[u8], offset: usize) -> Option<(Node, usize)> {
+    if bytes.get(offset..offset + 4)? != DIRECTORY_ENTRY {
+        return None;
+    }
+    let method = read(bytes, offset + 10, 2)?;
+    let compressed = read(bytes, offset + 20, 4)?;
+    let uncompressed = read(bytes, offset + 24, 4)?;
+    let name_len = read(bytes, offset + 28, 2)?;
+    let extra_len = read(bytes, offset + 30, 2)?;
+    let comment_len = read(bytes, offset + 32, 2)?;
+    let local_offset = read(bytes, offset + 42, 4)?;
+    let entry_len = 46 + name_len + extra_len + comment_len;
+
+    let mut directory_entry = node("central directory entry", offset, entry_len, "");
+    directory_entry.children = [
+        ("version made by", 4, 2),
+        ("compression", 10, 2),
+        ("CRC-32", 16, 4),
+        ("compressed size", 20, 4),
+        ("uncompressed size", 24, 4),
+        ("local header offset", 42, 4),
+    ]
+    .into_iter()
+    .filter_map(|(name, start, size)| field(bytes, name, offset + start, size))
+    .collect();
+
+    let mut file = node(
+        text(bytes, offset + 46, name_len),
+        local_offset,
+        0,
+        format!(
+            "{}, {} of {} bytes",
+            method_name(method),
+            compressed,
+            uncompressed
+        ),
+    );
+    file.children = local_file(bytes, local_offset, compressed)
+        .unwrap_or_else(|| vec![node("local header", local_offset, 0, "missing")]);
+    file.size = file.children.iter().map(|child| child.size).sum();
+    file.children.push(directory_entry);
+    Some((file, offset + entry_len))
+}
+
+pub fn parse(bytes: &[u8]) -> Node {
+    let mut root = node("ZIP archive", 0, bytes.len(), "");
+    let search_from = bytes.len().saturating_sub(MAX_END_SIZE);
+    let Some(end) = bytes[search_from..]
+        .windows(4)
+        .rposition(|window| window == END_OF_DIRECTORY)
+        .map(|position| search_from + position)
+    else {
+        root.value = "end of central directory not found".to_string();
+        return root;
+    };
+
+    let entries = read(bytes, end + 10, 2).unwrap_or_default();
+    let mut offset = read(bytes, end + 16, 4).unwrap_or_default();
+    for _ in 0..entries {
+        let Some((file, next)) = directory_entry(bytes, offset) else {
+            break;
+        };
+        root.children.push(file);
+        offset = next;
+    }
+    root.value = format!("{} files", root.children.len());
+
+    let mut end_node = node("end of central directory", end, 22, "");
+    end_node.children = [
+        ("entries", 10, 2),
+        ("directory size", 12, 4),
+        ("directory offset", 16, 4),
+        ("comment length", 20, 2),
+    ]
+    .into_iter()
+    .filter_map(|(name, start, size)| field(bytes, name, end + start, size))
+    .collect();
+    root.children.push(end_node);
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directory() {
+        let mut bytes = b"PK\x03\x04\x14\x00\x00\x00\x00\x00".to_vec();
+        bytes.extend([0; 4]); // time and date
+        bytes.extend([0; 4]); // CRC-32
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend([5, 0, 0, 0]);
+        bytes.extend(b"a.txtabc");
+
+        let directory = bytes.len();
+        bytes.extend(b"PK\x01\x02\x14\x00\x14\x00\x00\x00\x00\x00");
+        bytes.extend([0; 8]); // time, date and CRC-32
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend([5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        bytes.extend(b"a.txt");
+
+        let directory_size = bytes.len() - directory;
+        bytes.extend(b"PK\x05\x06\x00\x00\x00\x00\x01\x00\x01\x00");
+        bytes.extend((directory_size as u32).to_le_bytes());
+        bytes.extend((directory as u32).to_le_bytes());
+        bytes.extend([0, 0]);
+
+        let root = parse(&bytes);
+        assert_eq!(root.value, "1 files");
+        let file = &root.children[0];
+        assert_eq!(file.name, "a.txt");
+        assert_eq!(file.value, "stored, 3 of 3 bytes");
+        assert_eq!(file.size, 38);
+        assert_eq!((file.children[1].offset, file.children[1].size), (35, 3));
+        assert_eq!(file.children[2].offset, directory);
+        assert_eq!(root.children[1].offset, directory + directory_size);
+    }
+}
@@ -1,5 +1,3 @@
-use std::convert::From;
-
 pub fn convert_to_ascii(byte: u8) -> Option<char> {
     if byte <= 127 {
         Some(byte as char)
@@ -8,20 +6,26 @@ pub fn convert_to_ascii(byte: u8) -> Option<char> {
     }
 }
 
+/// Returns whether a byte is a printable ASCII character.
+pub fn is_printable(byte: u8) -> bool {
+    byte.is_ascii_graphic() || byte == b' '
+}
+
+/// Returns the character a byte is shown as next to its hexadecimal value,
+/// `.` for anything that is not printable ASCII.
+pub fn convert_to_printable(byte: u8) -> char {
+    convert_to_ascii(byte)
+        .filter(|_| is_printable(byte))
+        .unwrap_or('.')
+}
+
+/// Converts every byte into a character, bytes that aren't printable ASCII
+/// become `.` so that each character still corresponds to one byte.
 pub fn convert_bytes_to_ascii(
     bytes: &[u8],
     ascii_chars: &mut Vec<char>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    for &byte in bytes {
-        if !byte.is_ascii_alphabetic() {
-            // Skip to the next iteration if it's not a letter
-            continue;
-        }
-        match convert_to_ascii(byte) {
-            Some(ascii_char) => ascii_chars.push(ascii_char),
-            None => return Err(From::from("Failed to convert bytes to Ascii")),
-        }
-    }
+    ascii_chars.extend(bytes.iter().map(|&byte| convert_to_printable(byte)));
     Ok(())
 }
 
@@ -38,4 +42,12 @@ mod tests {
         assert!(result.is_ok()); // Ensure no error occurred
         assert_eq!(ascii_chars, expected_chars); // Check the converted characters
     }
+
+    #[test]
+    fn test_convert_bytes_to_ascii_keeps_every_byte() {
+        let bytes = vec![b'a', b'1', b' ', 0x00, 0x7F, 0xFF, b'!'];
+        let mut ascii_chars = Vec::new();
+        convert_bytes_to_ascii(&bytes, &mut ascii_chars).unwrap();
+        assert_eq!(ascii_chars, vec!['a', '1', ' ', '.', '.', '.', '!']);
+    }
 }
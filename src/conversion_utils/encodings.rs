@@ -0,0 +1,177 @@
+use super::{from_bytes::FromBytesError, window::ConvertedBytes, FromBytes};
+use crate::enums::endian::Endian;
+
+/// Code page 437 in the order of the bytes, with the graphical characters
+/// DOS displayed for the control bytes. DOS showed 0x00 as a blank, which is
+/// `␀` here so that every byte has a character of its own, and 0xFF is a
+/// no-break space.
+const CP437: [&str; 16] = [
+    "␀☺☻♥♦♣♠•◘○◙♂♀♪♫☼",
+    "►◄↕‼¶§▬↨↑↓→←∟↔▲▼",
+    " !\"#$%&'()*+,-./",
+    "0123456789:;<=>?",
+    "@ABCDEFGHIJKLMNO",
+    "PQRSTUVWXYZ[\\]^_",
+    "`abcdefghijklmno",
+    "pqrstuvwxyz{|}~⌂",
+    "ÇüéâäàåçêëèïîìÄÅ",
+    "ÉæÆôöòûùÿÖÜ¢£¥₧ƒ",
+    "áíóúñÑªº¿⌐¬½¼¡«»",
+    "░▒▓│┤╡╢╖╕╣║╗╝╜╛┐",
+    "└┴┬├─┼╞╟╚╔╩╦╠═╬╧",
+    "╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀",
+    "αßΓπΣσµτΦΘΩδ∞φε∩",
+    "≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{A0}",
+];
+
+/// Row of a byte or code unit that belongs to the character before it.
+pub const CONTINUATION: &str = "↳ part of the previous character";
+
+/// Replaces characters that would break the layout of a row with `.`.
+fn printable(c: char) -> char {
+    if c.is_control() {
+        '.'
+    } else {
+        c
+    }
+}
+
+pub fn convert_to_latin1(byte: u8) -> char {
+    // Latin-1 is the first 256 code points of Unicode
+    printable(byte as char)
+}
+
+pub fn convert_to_cp437(byte: u8) -> char {
+    CP437[byte as usize / 16]
+        .chars()
+        .nth(byte as usize % 16)
+        .unwrap()
+}
+
+/// Returns the byte `c` is stored as in code page 437, if it has one.
+pub fn cp437_byte(c: char) -> Option<u8> {
+    (0..=u8::MAX).find(|&byte| convert_to_cp437(byte) == c)
+}
+
+/// Converts UTF-8 into one row per byte. The first byte of a character shows
+/// it, the rest of its bytes are marked as belonging to it.
+pub fn convert_utf8(bytes: &[u8]) -> Vec<String> {
+    let mut rows = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let end = (index + 4).min(bytes.len());
+        let valid = match std::str::from_utf8(&bytes[index..end]) {
+            Ok(valid) => valid,
+            // Unwrap is fine since the bytes are valid up to that point
+            Err(e) => std::str::from_utf8(&bytes[index..index + e.valid_up_to()]).unwrap(),
+        };
+        match valid.chars().next() {
+            Some(c) if c.len_utf8() == 1 => rows.push(printable(c).to_string()),
+            Some(c) => {
+                rows.push(format!("{} ({} bytes)", printable(c), c.len_utf8()));
+                rows.extend((1..c.len_utf8()).map(|_| CONTINUATION.to_string()));
+            }
+            None => rows.push("� invalid".to_string()),
+        }
+        index += valid.chars().next().map_or(1, char::len_utf8);
+    }
+    rows
+}
+
+/// Converts UTF-16 into one row per code unit, surrogate pairs are shown on
+/// the row of their first unit.
+pub fn convert_utf16(bytes: &[u8], endian: Endian) -> ConvertedBytes {
+    let mut units: Vec<u16> = Vec::new();
+    let trailing = match u16::add_bytes(bytes, endian, &mut units) {
+        Ok(()) => 0,
+        Err(FromBytesError::Trailing(x)) => x,
+    };
+
+    let mut values = Vec::with_capacity(units.len());
+    let mut index = 0;
+    while index < units.len() {
+        let pair = &units[index..(index + 2).min(units.len())];
+        match char::decode_utf16(pair.iter().copied()).next() {
+            Some(Ok(c)) if c.len_utf16() == 2 => {
+                values.push(format!("{} (surrogate pair)", printable(c)));
+                values.push(CONTINUATION.to_string());
+                index += 2;
+            }
+            Some(Ok(c)) => {
+                values.push(printable(c).to_string());
+                index += 1;
+            }
+            _ => {
+                values.push("� unpaired surrogate".to_string());
+                index += 1;
+            }
+        }
+    }
+    ConvertedBytes {
+        values,
+        trailing: bytes[bytes.len() - trailing..].to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_byte_encodings() {
+        assert_eq!(convert_to_latin1(b'A'), 'A');
+        assert_eq!(convert_to_latin1(0xE9), 'é');
+        assert_eq!(convert_to_latin1(0x85), '.');
+        assert_eq!(convert_to_cp437(0x01), '☺');
+        assert_eq!(convert_to_cp437(b'A'), 'A');
+        assert_eq!(convert_to_cp437(0xDB), '█');
+        assert_eq!(cp437_byte('█'), Some(0xDB));
+        assert_eq!(cp437_byte('€'), None);
+    }
+
+    #[test]
+    fn test_cp437_round_trip() {
+        for byte in 0..=u8::MAX {
+            assert_eq!(cp437_byte(convert_to_cp437(byte)), Some(byte));
+        }
+        assert_eq!(cp437_byte(' '), Some(0x20));
+        assert_eq!(convert_to_cp437(0x00), '␀');
+        assert_eq!(convert_to_cp437(0xFF), '\u{A0}');
+    }
+
+    #[test]
+    fn test_convert_utf8() {
+        assert_eq!(
+            convert_utf8("aé€".as_bytes()),
+            vec![
+                "a",
+                "é (2 bytes)",
+                CONTINUATION,
+                "€ (3 bytes)",
+                CONTINUATION,
+                CONTINUATION
+            ]
+        );
+        assert_eq!(
+            convert_utf8(&[0xC3, b'a', 0x80]),
+            vec!["� invalid", "a", "� invalid"]
+        );
+    }
+
+    #[test]
+    fn test_convert_utf16() {
+        let bytes = [0x41, 0x00, 0x3D, 0xD8, 0x00, 0xDE, 0x00, 0xDC, 0x42];
+        assert_eq!(
+            convert_utf16(&bytes, Endian::Little),
+            ConvertedBytes {
+                values: vec![
+                    "A".to_string(),
+                    "😀 (surrogate pair)".to_string(),
+                    CONTINUATION.to_string(),
+                    "� unpaired surrogate".to_string(),
+                ],
+                trailing: vec![0x42],
+            }
+        );
+    }
+}
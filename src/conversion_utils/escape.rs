@@ -0,0 +1,31 @@
+/// Puts `text` in double quotes with the escapes of JSON, which TOML basic
+/// strings share.
+pub fn quoted(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted() {
+        assert_eq!(quoted("abc"), "\"abc\"");
+        assert_eq!(quoted("a \"b\"\\"), "\"a \\\"b\\\"\\\\\"");
+        assert_eq!(quoted("\n\t\u{1}é"), "\"\\n\\t\\u0001é\"");
+    }
+}
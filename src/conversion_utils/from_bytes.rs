@@ -20,7 +20,7 @@ pub trait FromBytes<const N: usize> {
     /// # Output
     ///
     /// If no errors occur, an iterator of [Self]. If the array given to it is
-    /// not a multiple of [N], returns the [FromBytesError::Trailing]
+    /// not a multiple of `N`, returns the [FromBytesError::Trailing]
     /// error.
     fn from_multiple_bytes(
         bytes: &[u8],
@@ -1,3 +1,6 @@
+#[cfg(feature = "tui")]
+use super::ascii::convert_to_printable;
+
 pub fn convert_to_hex(byte: u8) -> String {
     format!("{:02X}", byte)
 }
@@ -13,6 +16,30 @@ pub fn convert_bytes_to_hex(
     Ok(())
 }
 
+/// Formats one row of a classic hexdump: the offset of the row, the bytes in
+/// hexadecimal split into groups of eight and the printable characters.
+/// Rows shorter than `bytes_per_row` are padded so that the columns line up.
+#[cfg(feature = "tui")]
+pub fn hexdump_line(offset: usize, bytes: &[u8], bytes_per_row: usize) -> String {
+    let mut line = format!("{:08X} ", offset);
+    for index in 0..bytes_per_row {
+        if index % 8 == 0 {
+            line.push(' ');
+        }
+        match bytes.get(index) {
+            Some(&byte) => {
+                line.push_str(&convert_to_hex(byte));
+                line.push(' ');
+            }
+            None => line.push_str("   "),
+        }
+    }
+    line.push('|');
+    line.extend(bytes.iter().map(|&byte| convert_to_printable(byte)));
+    line.push('|');
+    line
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -29,4 +56,17 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(hex_strings, expected_hex);
     }
+
+    #[test]
+    #[cfg(feature = "tui")]
+    fn test_hexdump_line() {
+        assert_eq!(
+            hexdump_line(0x10, b"Hello, world!\n\x00\xFF", 16),
+            "00000010  48 65 6C 6C 6F 2C 20 77  6F 72 6C 64 21 0A 00 FF |Hello, world!...|"
+        );
+        assert_eq!(
+            hexdump_line(0x20, b"AB", 8),
+            "00000020  41 42                   |AB|"
+        );
+    }
 }
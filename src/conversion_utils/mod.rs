@@ -1,8 +1,14 @@
 pub mod ascii;
+pub mod encodings;
+pub mod escape;
 pub mod from_bytes;
 pub mod from_one_byte_to_i8;
 pub mod hexadecimal;
+#[cfg(feature = "tui")]
+pub mod strings;
 pub mod three_byte_numbers;
+pub mod to_bytes;
+pub mod window;
 
 pub use from_bytes::FromBytes;
 pub use three_byte_numbers::{I24, U24};
@@ -0,0 +1,115 @@
+use strum_macros::EnumIter;
+
+use super::ascii::is_printable;
+
+#[derive(EnumIter, Debug, Clone, Copy, PartialEq)]
+pub enum StringEncoding {
+    Ascii,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl StringEncoding {
+    /// Number of bytes a single character takes up.
+    pub fn size(&self) -> usize {
+        match self {
+            StringEncoding::Ascii => 1,
+            StringEncoding::Utf16Le | StringEncoding::Utf16Be => 2,
+        }
+    }
+
+    /// Returns the printable ASCII character stored in `bytes`, if any.
+    /// UTF-16 is limited to the ASCII range like `strings -e l` does.
+    fn printable_char(&self, bytes: &[u8]) -> Option<char> {
+        let byte = match (self, bytes) {
+            (StringEncoding::Ascii, [byte]) => *byte,
+            (StringEncoding::Utf16Le, [byte, 0]) => *byte,
+            (StringEncoding::Utf16Be, [0, byte]) => *byte,
+            _ => return None,
+        };
+        is_printable(byte).then_some(byte as char)
+    }
+}
+
+/// A run of printable characters and the offset of its first byte.
+#[derive(Debug, PartialEq)]
+pub struct FoundString {
+    pub offset: usize,
+    pub text: String,
+}
+
+/// Finds every run of at least `min_length` printable characters, like
+/// `strings(1)` does.
+pub fn find_strings(bytes: &[u8], encoding: StringEncoding, min_length: usize) -> Vec<FoundString> {
+    let size = encoding.size();
+    let mut found = Vec::new();
+    let mut start = 0;
+    let mut text = String::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let end = (offset + size).min(bytes.len());
+        match encoding.printable_char(&bytes[offset..end]) {
+            Some(c) => {
+                if text.is_empty() {
+                    start = offset;
+                }
+                text.push(c);
+                offset += size;
+            }
+            None => {
+                if text.len() >= min_length {
+                    found.push(FoundString {
+                        offset: start,
+                        text: std::mem::take(&mut text),
+                    });
+                }
+                text.clear();
+                // Strings in UTF-16 may start at odd offsets as well
+                offset += 1;
+            }
+        }
+    }
+    if text.len() >= min_length {
+        found.push(FoundString {
+            offset: start,
+            text,
+        });
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_ascii_strings() {
+        let bytes = b"\x00\x01Hello world\xFFab\x00tail";
+        assert_eq!(
+            find_strings(bytes, StringEncoding::Ascii, 4),
+            vec![
+                FoundString {
+                    offset: 2,
+                    text: "Hello world".to_string()
+                },
+                FoundString {
+                    offset: 17,
+                    text: "tail".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_utf16_strings() {
+        let bytes = b"\xFFH\x00i\x00!\x00\x00\x00";
+        assert_eq!(
+            find_strings(bytes, StringEncoding::Utf16Le, 3),
+            vec![FoundString {
+                offset: 1,
+                text: "Hi!".to_string()
+            }]
+        );
+        assert_eq!(find_strings(bytes, StringEncoding::Utf16Be, 3), vec![]);
+    }
+}
@@ -12,6 +12,12 @@ impl fmt::Display for U24 {
     }
 }
 
+impl From<U24> for u32 {
+    fn from(value: U24) -> u32 {
+        value.0
+    }
+}
+
 impl FromBytes<3> for U24 {
     fn from_bytes(bytes: [u8; 3], endian: Endian) -> Self {
         match endian {
@@ -36,6 +42,12 @@ impl fmt::Display for I24 {
     }
 }
 
+impl From<I24> for i32 {
+    fn from(value: I24) -> i32 {
+        value.0
+    }
+}
+
 impl FromBytes<3> for I24 {
     fn from_bytes(bytes: [u8; 3], endian: Endian) -> Self {
         match endian {
@@ -0,0 +1,191 @@
+use thiserror::Error;
+
+use super::encodings::cp437_byte;
+use crate::enums::{endian::Endian, format::Format};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ToBytesError {
+    #[error("'{0}' is not a valid {1:?} value")]
+    InvalidValue(String, Format),
+}
+
+/// Parses an integer, `0x` prefixed hexadecimal is accepted as well.
+fn parse_integer<T: TryFrom<i128>>(value: &str) -> Option<T> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let magnitude = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i128>().ok()?,
+    };
+    T::try_from(if negative { -magnitude } else { magnitude }).ok()
+}
+
+/// Parses hexadecimal digits into bytes, whitespace between them is ignored.
+fn parse_hex(value: &str) -> Option<Vec<u8>> {
+    let digits: Vec<char> = value.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(&pair.iter().collect::<String>(), 16).ok())
+        .collect()
+}
+
+/// This macro converts a parsed number with its `to_be_bytes` and
+/// `to_le_bytes` functions, the counterpart of `implement_from_bytes`.
+macro_rules! number_to_bytes {
+    ($value:expr, $endian:expr) => {
+        match $endian {
+            Endian::Big => $value.to_be_bytes().to_vec(),
+            Endian::Little => $value.to_le_bytes().to_vec(),
+        }
+    };
+}
+
+/// Encodes `text` in the character format `format` as it is, unlike
+/// [value_to_bytes] which trims it first, so that spaces can be typed.
+pub fn text_to_bytes(text: &str, format: Format) -> Result<Vec<u8>, ToBytesError> {
+    let invalid = || ToBytesError::InvalidValue(text.to_string(), format);
+    let bytes = match format {
+        Format::Ascii => {
+            if text.is_empty() || !text.is_ascii() {
+                return Err(invalid());
+            }
+            text.as_bytes().to_vec()
+        }
+        Format::Latin1 => text
+            .chars()
+            .map(|c| u8::try_from(c).ok())
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?,
+        Format::Cp437 => text
+            .chars()
+            .map(cp437_byte)
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?,
+        Format::Utf8 => text.as_bytes().to_vec(),
+        Format::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        Format::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        _ => return Err(invalid()),
+    };
+    Ok(bytes)
+}
+
+/// Turns the textual representation of a value of `format` into the bytes it
+/// is stored as, the inverse of what is displayed for that format.
+///
+/// Hex values are hexadecimal digits and character formats encode the whole
+/// text, both may be longer than a single value.
+pub fn value_to_bytes(
+    value: &str,
+    format: Format,
+    endian: Endian,
+) -> Result<Vec<u8>, ToBytesError> {
+    let value = value.trim();
+    let invalid = || ToBytesError::InvalidValue(value.to_string(), format);
+    let bytes = match format {
+        Format::Hex => parse_hex(value).ok_or_else(invalid)?,
+        Format::Ascii
+        | Format::Latin1
+        | Format::Cp437
+        | Format::Utf8
+        | Format::Utf16Le
+        | Format::Utf16Be => text_to_bytes(value, format)?,
+        Format::Int8 => vec![parse_integer::<i8>(value).ok_or_else(invalid)? as u8],
+        Format::Uint8 => vec![parse_integer::<u8>(value).ok_or_else(invalid)?],
+        Format::Int16 => number_to_bytes!(parse_integer::<i16>(value).ok_or_else(invalid)?, endian),
+        Format::Uint16 => {
+            number_to_bytes!(parse_integer::<u16>(value).ok_or_else(invalid)?, endian)
+        }
+        Format::Int24 | Format::Uint24 => {
+            let number = parse_integer::<i32>(value).ok_or_else(invalid)?;
+            let in_range = match format {
+                Format::Int24 => (-0x80_0000..=0x7F_FFFF).contains(&number),
+                _ => (0..=0xFF_FFFF).contains(&number),
+            };
+            if !in_range {
+                return Err(invalid());
+            }
+            let [_, high, middle, low] = number.to_be_bytes();
+            match endian {
+                Endian::Big => vec![high, middle, low],
+                Endian::Little => vec![low, middle, high],
+            }
+        }
+        Format::Int32 => number_to_bytes!(parse_integer::<i32>(value).ok_or_else(invalid)?, endian),
+        Format::Uint32 => {
+            number_to_bytes!(parse_integer::<u32>(value).ok_or_else(invalid)?, endian)
+        }
+        Format::Int64 => number_to_bytes!(parse_integer::<i64>(value).ok_or_else(invalid)?, endian),
+        Format::Uint64 => {
+            number_to_bytes!(parse_integer::<u64>(value).ok_or_else(invalid)?, endian)
+        }
+        Format::F32 => number_to_bytes!(value.parse::<f32>().map_err(|_| invalid())?, endian),
+        Format::F64 => number_to_bytes!(value.parse::<f64>().map_err(|_| invalid())?, endian),
+    };
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion_utils::{FromBytes, I24};
+
+    #[test]
+    fn test_value_to_bytes() {
+        assert_eq!(
+            value_to_bytes("1337", Format::Uint32, Endian::Big),
+            Ok(vec![0, 0, 5, 57])
+        );
+        assert_eq!(
+            value_to_bytes("0x1337", Format::Uint16, Endian::Little),
+            Ok(vec![0x37, 0x13])
+        );
+        assert_eq!(
+            value_to_bytes("-2", Format::Int8, Endian::Big),
+            Ok(vec![0xFE])
+        );
+        assert_eq!(
+            value_to_bytes("de ad BE EF", Format::Hex, Endian::Big),
+            Ok(vec![0xDE, 0xAD, 0xBE, 0xEF])
+        );
+        assert_eq!(
+            value_to_bytes("1.5", Format::F32, Endian::Big),
+            Ok(vec![0x3F, 0xC0, 0, 0])
+        );
+        assert_eq!(
+            value_to_bytes("hé", Format::Utf16Be, Endian::Little),
+            Ok(vec![0, b'h', 0, 0xE9])
+        );
+        assert_eq!(
+            value_to_bytes("a b", Format::Cp437, Endian::Big),
+            Ok(vec![b'a', b' ', b'b'])
+        );
+        assert_eq!(text_to_bytes(" ", Format::Ascii), Ok(vec![b' ']));
+        assert_eq!(
+            value_to_bytes("€", Format::Latin1, Endian::Big),
+            Err(ToBytesError::InvalidValue("€".to_string(), Format::Latin1))
+        );
+        assert_eq!(
+            value_to_bytes("256", Format::Uint8, Endian::Big),
+            Err(ToBytesError::InvalidValue("256".to_string(), Format::Uint8))
+        );
+    }
+
+    #[test]
+    fn test_three_byte_values_round_trip() {
+        let bytes = value_to_bytes("-2", Format::Int24, Endian::Little).unwrap();
+        assert_eq!(bytes, vec![0xFE, 0xFF, 0xFF]);
+        assert_eq!(
+            I24::from_bytes(bytes.try_into().unwrap(), Endian::Little).to_string(),
+            "-2"
+        );
+        assert!(value_to_bytes("16777216", Format::Uint24, Endian::Big).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use super::{
+    ascii::convert_bytes_to_ascii,
+    encodings::{convert_to_cp437, convert_to_latin1, convert_utf16, convert_utf8},
+    from_bytes::FromBytesError,
+    from_one_byte_to_i8::add_byte_as_i8,
+    hexadecimal::convert_bytes_to_hex,
+    FromBytes, I24, U24,
+};
+use crate::enums::{endian::Endian, format::Format};
+
+/// Values converted from a part of the file.
+#[derive(Debug, PartialEq)]
+pub struct ConvertedBytes {
+    pub values: Vec<String>,
+    /// Bytes at the end that are too few to make up another value.
+    pub trailing: Vec<u8>,
+}
+
+impl From<Vec<String>> for ConvertedBytes {
+    fn from(values: Vec<String>) -> Self {
+        ConvertedBytes {
+            values,
+            trailing: Vec::new(),
+        }
+    }
+}
+
+fn decode<T: FromBytes<N> + Display, const N: usize>(
+    bytes: &[u8],
+    endian: Endian,
+) -> ConvertedBytes {
+    let mut numbers: Vec<T> = Vec::new();
+    // Callers only hand over the bytes of the rows they display, so trailing
+    // bytes can only show up at the very end of the file
+    let trailing = match T::add_bytes(bytes, endian, &mut numbers) {
+        Ok(()) => 0,
+        Err(FromBytesError::Trailing(x)) => x,
+    };
+    ConvertedBytes {
+        values: numbers.iter().map(ToString::to_string).collect(),
+        trailing: bytes[bytes.len() - trailing..].to_vec(),
+    }
+}
+
+/// Converts `bytes` into the textual representation of every value of
+/// `format` found in them. Only the part of the file that is actually
+/// displayed should be passed here, which keeps large files cheap to view.
+///
+/// Character formats need [Format::lookahead] bytes after the displayed rows
+/// to decode characters that continue past them, the rows for those extra
+/// bytes should be dropped by the caller.
+pub fn convert_bytes(
+    bytes: &[u8],
+    format: Format,
+    endian: Endian,
+) -> Result<ConvertedBytes, Box<dyn Error>> {
+    let converted = match format {
+        Format::Hex => {
+            let mut hex_strings = Vec::new();
+            convert_bytes_to_hex(bytes, &mut hex_strings)?;
+            hex_strings.into()
+        }
+        Format::Ascii => {
+            let mut ascii_chars = Vec::new();
+            convert_bytes_to_ascii(bytes, &mut ascii_chars)?;
+            ascii_chars
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .into()
+        }
+        Format::Latin1 => bytes
+            .iter()
+            .map(|&byte| convert_to_latin1(byte).to_string())
+            .collect::<Vec<_>>()
+            .into(),
+        Format::Cp437 => bytes
+            .iter()
+            .map(|&byte| convert_to_cp437(byte).to_string())
+            .collect::<Vec<_>>()
+            .into(),
+        Format::Utf8 => convert_utf8(bytes).into(),
+        Format::Utf16Le => convert_utf16(bytes, Endian::Little),
+        Format::Utf16Be => convert_utf16(bytes, Endian::Big),
+        Format::Int8 => {
+            let mut numbers = Vec::new();
+            add_byte_as_i8(bytes, &mut numbers)?;
+            numbers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .into()
+        }
+        Format::Uint8 => bytes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .into(),
+        Format::Int16 => decode::<i16, 2>(bytes, endian),
+        Format::Uint16 => decode::<u16, 2>(bytes, endian),
+        Format::Int24 => decode::<I24, 3>(bytes, endian),
+        Format::Uint24 => decode::<U24, 3>(bytes, endian),
+        Format::Int32 => decode::<i32, 4>(bytes, endian),
+        Format::Uint32 => decode::<u32, 4>(bytes, endian),
+        Format::Int64 => decode::<i64, 8>(bytes, endian),
+        Format::Uint64 => decode::<u64, 8>(bytes, endian),
+        Format::F32 => decode::<f32, 4>(bytes, endian),
+        Format::F64 => decode::<f64, 8>(bytes, endian),
+    };
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_bytes_only_decodes_given_window() {
+        let bytes = [0xFF, 0xFE, 0x00, 0x01, 0x02];
+        assert_eq!(
+            convert_bytes(&bytes[..3], Format::Int24, Endian::Big)
+                .unwrap()
+                .values,
+            vec!["-512"]
+        );
+        assert_eq!(
+            convert_bytes(&bytes[..4], Format::Uint16, Endian::Little)
+                .unwrap()
+                .values,
+            vec!["65279", "256"]
+        );
+        assert_eq!(
+            convert_bytes(&bytes[..2], Format::Hex, Endian::Big)
+                .unwrap()
+                .values,
+            vec!["FF", "FE"]
+        );
+    }
+
+    #[test]
+    fn test_convert_bytes_keeps_trailing_bytes() {
+        let bytes = [0xFF, 0xFE, 0x00, 0x01, 0x02];
+        assert_eq!(
+            convert_bytes(&bytes, Format::Uint32, Endian::Big).unwrap(),
+            ConvertedBytes {
+                values: vec!["4294836225".to_string()],
+                trailing: vec![0x02],
+            }
+        );
+        assert_eq!(
+            convert_bytes(&bytes, Format::Int24, Endian::Big)
+                .unwrap()
+                .trailing,
+            vec![0x01, 0x02]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    conversion_utils::{encodings::CONTINUATION, window::convert_bytes},
+    enums::{endian::Endian, format::Format},
+};
+
+/// A formatted value and the offset of its first byte.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Value {
+    pub offset: usize,
+    pub text: String,
+}
+
+/// Decodes the values of a [Format] from a byte slice, the same way they are
+/// shown in the interface.
+///
+/// Values are decoded one at a time as the iterator advances, so only the
+/// values that are actually used are converted.
+///
+/// ```
+/// use byteblitz::{Decoder, Endian, Format};
+///
+/// let bytes = [0xFF, 0x01, 0x00, 0x02, 0x00, 0x03];
+/// let values: Vec<String> = Decoder::new(&bytes, Format::Uint16, Endian::Little, 1)
+///     .map(|value| value.text)
+///     .collect();
+/// assert_eq!(values, ["1", "2"]);
+/// ```
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    format: Format,
+    endian: Endian,
+    position: usize,
+    /// Number of the following values that belong to the last character.
+    continuations: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Decodes `bytes` as `format`, starting at `offset`. The offsets of the
+    /// values are relative to the start of `bytes`.
+    pub fn new(bytes: &'a [u8], format: Format, endian: Endian, offset: usize) -> Decoder<'a> {
+        Decoder {
+            bytes,
+            format,
+            endian,
+            position: offset.min(bytes.len()),
+            continuations: 0,
+        }
+    }
+
+    /// Bytes at the end that are too few to make up another value.
+    pub fn trailing(&self) -> &'a [u8] {
+        let remaining = self.bytes.len() - self.position;
+        &self.bytes[self.bytes.len() - remaining % self.format.size()..]
+    }
+}
+
+impl Iterator for Decoder<'_> {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        let size = self.format.size();
+        let end = self.position.checked_add(size)?;
+        if end > self.bytes.len() {
+            return None;
+        }
+        let text = if self.continuations > 0 {
+            self.continuations -= 1;
+            CONTINUATION.to_string()
+        } else {
+            // Characters can continue past the value, so the lookahead is
+            // converted along with it. Its rows only tell how many of the
+            // following values are part of the character.
+            let with_lookahead = (end + self.format.lookahead()).min(self.bytes.len());
+            let mut values = convert_bytes(
+                &self.bytes[self.position..with_lookahead],
+                self.format,
+                self.endian,
+            )
+            .ok()?
+            .values
+            .into_iter();
+            let text = values.next()?;
+            self.continuations = values.take_while(|value| value == CONTINUATION).count();
+            text
+        };
+        let value = Value {
+            offset: self.position,
+            text,
+        };
+        self.position = end;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8], format: Format, offset: usize) -> Vec<(usize, String)> {
+        Decoder::new(bytes, format, Endian::Big, offset)
+            .map(|value| (value.offset, value.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_formats() {
+        let bytes = [0x00, 0x01, 0xFF, 0xFE, 0x41];
+        assert_eq!(
+            decode(&bytes, Format::Int16, 0),
+            vec![(0, "1".to_string()), (2, "-2".to_string())]
+        );
+        assert_eq!(
+            decode(&bytes, Format::Uint24, 2),
+            vec![(2, "16776769".to_string())]
+        );
+        assert_eq!(decode(&bytes, Format::Ascii, 4), vec![(4, "A".to_string())]);
+        assert!(decode(&bytes, Format::F64, 0).is_empty());
+
+        let decoder = Decoder::new(&bytes, Format::Uint32, Endian::Big, 0);
+        assert_eq!(decoder.trailing(), &[0x41]);
+    }
+
+    #[test]
+    fn test_characters_use_the_following_bytes() {
+        assert_eq!(
+            decode("é!".as_bytes(), Format::Utf8, 0),
+            vec![
+                (0, "é (2 bytes)".to_string()),
+                (1, CONTINUATION.to_string()),
+                (2, "!".to_string())
+            ]
+        );
+        assert_eq!(
+            decode(&[0xD8, 0x3D, 0xDE, 0x00, 0x00, 0x41], Format::Utf16Be, 0),
+            vec![
+                (0, "😀 (surrogate pair)".to_string()),
+                (2, CONTINUATION.to_string()),
+                (4, "A".to_string())
+            ]
+        );
+        // A character that started before the offset can't be told apart
+        // from a stray continuation byte
+        assert_eq!(
+            decode("é".as_bytes(), Format::Utf8, 1),
+            vec![(1, "� invalid".to_string())]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::ops::Range;
+
+/// Ranges of bytes that differ between two files compared offset by offset.
+pub struct Diff {
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl Diff {
+    /// Compares `a` and `b`. Bytes that only one of them has count as
+    /// different.
+    pub fn new(a: &[u8], b: &[u8]) -> Diff {
+        const CHUNK: usize = 4096;
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        let mut mark = |offset: usize| match ranges.last_mut() {
+            Some(range) if range.end == offset => range.end += 1,
+            _ => ranges.push(offset..offset + 1),
+        };
+
+        // Equal chunks are skipped as a whole, most of two builds is the same
+        for (index, (chunk_a, chunk_b)) in a.chunks(CHUNK).zip(b.chunks(CHUNK)).enumerate() {
+            if chunk_a == chunk_b {
+                continue;
+            }
+            for (offset, (byte_a, byte_b)) in chunk_a.iter().zip(chunk_b).enumerate() {
+                if byte_a != byte_b {
+                    mark(index * CHUNK + offset);
+                }
+            }
+        }
+
+        let (shorter, longer) = (a.len().min(b.len()), a.len().max(b.len()));
+        if shorter < longer {
+            match ranges.last_mut() {
+                Some(range) if range.end == shorter => range.end = longer,
+                _ => ranges.push(shorter..longer),
+            }
+        }
+        Diff { ranges }
+    }
+
+    /// Total number of bytes that differ.
+    pub fn changed_bytes(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+
+    /// Returns whether any difference overlaps the `len` bytes starting at
+    /// `start`.
+    pub fn overlaps(&self, start: usize, len: usize) -> bool {
+        let first = self.ranges.partition_point(|range| range.end <= start);
+        self.ranges
+            .get(first)
+            .is_some_and(|range| range.start < start + len)
+    }
+
+    /// Index of the first difference after `offset`, wrapping around at the
+    /// end of the file.
+    pub fn next(&self, offset: usize) -> Option<usize> {
+        if self.ranges.is_empty() {
+            return None;
+        }
+        let index = self.ranges.partition_point(|range| range.start <= offset);
+        Some(index % self.ranges.len())
+    }
+
+    /// Index of the last difference before `offset`, wrapping around at the
+    /// start of the file.
+    pub fn previous(&self, offset: usize) -> Option<usize> {
+        let index = self.ranges.partition_point(|range| range.start < offset);
+        match index {
+            0 => self.ranges.len().checked_sub(1),
+            index => Some(index - 1),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        match self.ranges.len() {
+            0 => "Files are identical".to_string(),
+            count => format!(
+                "{} changed ranges, {} bytes differ",
+                count,
+                self.changed_bytes()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_ranges() {
+        let a = [0, 1, 2, 3, 4, 5, 6, 7];
+        let b = [0, 9, 9, 3, 4, 5, 9, 7, 8, 8];
+        let diff = Diff::new(&a, &b);
+        assert_eq!(diff.ranges, vec![1..3, 6..7, 8..10]);
+        assert_eq!(diff.changed_bytes(), 5);
+        assert!(diff.overlaps(0, 2));
+        assert!(!diff.overlaps(3, 3));
+        assert!(Diff::new(&a, &a).ranges.is_empty());
+
+        let diff = Diff::new(&a, &[0, 1, 2, 3, 4, 5, 6, 0, 1]);
+        assert_eq!(diff.ranges, vec![7..9]);
+    }
+
+    #[test]
+    fn test_navigation_wraps_around() {
+        let diff = Diff {
+            ranges: vec![1..3, 6..7, 8..10],
+        };
+        assert_eq!(diff.next(0), Some(0));
+        assert_eq!(diff.next(1), Some(1));
+        assert_eq!(diff.next(8), Some(0));
+        assert_eq!(diff.previous(8), Some(1));
+        assert_eq!(diff.previous(1), Some(2));
+        assert_eq!(Diff { ranges: Vec::new() }.next(0), None);
+    }
+}
@@ -0,0 +1,211 @@
+use std::error::Error;
+use std::io::{self, Write};
+
+use strum_macros::EnumString;
+
+use crate::{
+    byte_source::ByteSource,
+    config::DumpConfig,
+    conversion_utils::escape::quoted,
+    decoder::{Decoder, Value},
+    enums::format::Format,
+};
+
+/// How `byteblitz dump` prints the values.
+#[derive(EnumString, Debug, Clone, Copy, PartialEq)]
+#[strum(ascii_case_insensitive)]
+pub enum Output {
+    /// One value per line.
+    Plain,
+    /// An `offset,value` header followed by a row per value.
+    Csv,
+    /// An array of `{"offset": .., "value": ..}` objects.
+    Json,
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Numbers are written as JSON numbers, everything else (characters, hex
+/// bytes, NaN and infinities) as strings.
+fn json_value(text: &str, format: Format) -> String {
+    let is_number =
+        format != Format::Hex && !format.is_text() && text.parse::<f64>().is_ok_and(f64::is_finite);
+    if is_number {
+        text.to_string()
+    } else {
+        quoted(text)
+    }
+}
+
+/// Writes `values` of `format` to `out` as they are decoded, so that large
+/// ranges are never held in memory.
+pub fn write_values(
+    out: &mut impl Write,
+    values: impl IntoIterator<Item = Value>,
+    format: Format,
+    output: Output,
+) -> io::Result<()> {
+    match output {
+        Output::Plain => {
+            for value in values {
+                writeln!(out, "{}", value.text)?;
+            }
+        }
+        Output::Csv => {
+            writeln!(out, "offset,value")?;
+            for value in values {
+                writeln!(out, "{},{}", value.offset, csv_field(&value.text))?;
+            }
+        }
+        Output::Json => {
+            writeln!(out, "[")?;
+            let mut first = true;
+            for value in values {
+                // Whether a value is the last one is only known once the
+                // next one isn't there, so the separator goes before it
+                if !first {
+                    writeln!(out, ",")?;
+                }
+                first = false;
+                write!(
+                    out,
+                    "  {{\"offset\": {}, \"value\": {}}}",
+                    value.offset,
+                    json_value(&value.text, format)
+                )?;
+            }
+            if !first {
+                writeln!(out)?;
+            }
+            writeln!(out, "]")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the values selected by `config` to `out`, returning how many bytes
+/// at the end don't make up a whole value.
+fn write_dump(config: &DumpConfig, out: &mut impl Write) -> io::Result<usize> {
+    let size = config.format.size();
+    let limit = config
+        .count
+        .map(|count| config.offset.saturating_add(count.saturating_mul(size)));
+    let source = ByteSource::open(&config.file_path, limit)?;
+
+    // Decoding from the offset within the whole file gives the values their
+    // offsets in the file
+    let mut decoder = Decoder::new(
+        source.as_slice(),
+        config.format,
+        config.endian,
+        config.offset,
+    );
+    write_values(out, decoder.by_ref(), config.format, config.output)?;
+    Ok(decoder.trailing().len())
+}
+
+/// Prints the values selected by `config` to stdout.
+pub fn run(config: DumpConfig) -> Result<(), Box<dyn Error>> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    let written = write_dump(&config, &mut out).and_then(|trailing| {
+        out.flush()?;
+        Ok(trailing)
+    });
+    let trailing = match written {
+        // The reader, e.g. `head`, has seen enough
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+        written => written?,
+    };
+    if trailing > 0 {
+        eprintln!(
+            "{trailing} trailing bytes at the end don't make up a whole {:?}",
+            config.format
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::endian::Endian;
+
+    fn dump(bytes: &[u8], format: Format, output: Output) -> String {
+        let config = DumpConfig {
+            file_path: String::new(),
+            format,
+            endian: Endian::Little,
+            offset: 0x10,
+            count: None,
+            output,
+        };
+        let values = Decoder::new(bytes, config.format, config.endian, 0).map(|value| Value {
+            offset: config.offset + value.offset,
+            text: value.text,
+        });
+        let mut out = Vec::new();
+        write_values(&mut out, values, format, output).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_outputs() {
+        let bytes = [1, 0, 0xFF, 0xFF, 7];
+        assert_eq!(dump(&bytes, Format::Int16, Output::Plain), "1\n-1\n");
+        assert_eq!(
+            dump(&bytes, Format::Uint16, Output::Csv),
+            "offset,value\n16,1\n18,65535\n"
+        );
+        assert_eq!(
+            dump(&bytes, Format::Int16, Output::Json),
+            "[\n  {\"offset\": 16, \"value\": 1},\n  {\"offset\": 18, \"value\": -1}\n]\n"
+        );
+        assert_eq!(
+            dump(b"\",", Format::Ascii, Output::Csv),
+            "offset,value\n16,\"\"\"\"\n17,\",\"\n"
+        );
+        assert_eq!(
+            dump(b"\"", Format::Ascii, Output::Json),
+            "[\n  {\"offset\": 16, \"value\": \"\\\"\"}\n]\n"
+        );
+        assert_eq!(dump(&[], Format::Int16, Output::Json), "[\n]\n");
+    }
+
+    #[test]
+    fn test_offset_and_count() {
+        let path = std::env::temp_dir().join(format!("byteblitz-dump-{}", std::process::id()));
+        std::fs::write(&path, [0, 1, 0, 2, 0, 3, 0, 4, 5]).unwrap();
+        let dump = |range: &[&str]| {
+            let path = path.to_str().unwrap();
+            let args: Vec<String> = ["byteblitz", "dump", path, "-f", "u16", "-e", "big"]
+                .iter()
+                .chain(range)
+                .chain(&["--output", "csv"])
+                .map(|arg| arg.to_string())
+                .collect();
+            let mut out = Vec::new();
+            let trailing = write_dump(&DumpConfig::new(&args).unwrap(), &mut out).unwrap();
+            (String::from_utf8(out).unwrap(), trailing)
+        };
+
+        assert_eq!(
+            dump(&["--offset", "2", "--count", "2"]),
+            ("offset,value\n2,2\n4,3\n".to_string(), 0)
+        );
+        assert_eq!(
+            dump(&["--offset", "0x6", "--count", "8"]),
+            ("offset,value\n6,4\n".to_string(), 1)
+        );
+        assert_eq!(
+            dump(&["--offset", "0x100", "--count", "2"]),
+            ("offset,value\n".to_string(), 0)
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
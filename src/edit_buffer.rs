@@ -0,0 +1,447 @@
+use std::borrow::Cow;
+use std::fs::{self, File};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::byte_source::ByteSource;
+
+/// A run of bytes taken either from the file or from the bytes added while
+/// editing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Piece {
+    Original { start: usize, len: usize },
+    Added { start: usize, len: usize },
+}
+
+impl Piece {
+    fn len(&self) -> usize {
+        match self {
+            Piece::Original { len, .. } | Piece::Added { len, .. } => *len,
+        }
+    }
+
+    /// Splits the piece into the bytes before and from `at`.
+    fn split(self, at: usize) -> (Piece, Piece) {
+        match self {
+            Piece::Original { start, len } => (
+                Piece::Original { start, len: at },
+                Piece::Original {
+                    start: start + at,
+                    len: len - at,
+                },
+            ),
+            Piece::Added { start, len } => (
+                Piece::Added { start, len: at },
+                Piece::Added {
+                    start: start + at,
+                    len: len - at,
+                },
+            ),
+        }
+    }
+
+    /// Joins `next` onto the piece if its bytes directly follow the piece's.
+    fn merge(self, next: Piece) -> Option<Piece> {
+        match (self, next) {
+            (
+                Piece::Original { start, len },
+                Piece::Original {
+                    start: next,
+                    len: more,
+                },
+            ) if start + len == next => Some(Piece::Original {
+                start,
+                len: len + more,
+            }),
+            (
+                Piece::Added { start, len },
+                Piece::Added {
+                    start: next,
+                    len: more,
+                },
+            ) if start + len == next => Some(Piece::Added {
+                start,
+                len: len + more,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Appends `piece` to `pieces`, extending the last one where possible so
+/// that typing byte by byte doesn't add a piece per byte.
+fn push_piece(pieces: &mut Vec<Piece>, piece: Piece) {
+    if piece.len() == 0 {
+        return;
+    }
+    if let Some(last) = pieces.last_mut() {
+        if let Some(merged) = last.merge(piece) {
+            *last = merged;
+            return;
+        }
+    }
+    pieces.push(piece);
+}
+
+/// Appends the bytes `from..to` of the run of `pieces` to `into`.
+fn push_range(into: &mut Vec<Piece>, pieces: &[Piece], from: usize, to: usize) {
+    let mut start = 0;
+    for &piece in pieces {
+        let end = start + piece.len();
+        if start < to && from < end {
+            let (_, rest) = piece.split(from.saturating_sub(start));
+            let (kept, _) = rest.split(to.min(end) - start.max(from));
+            push_piece(into, kept);
+        }
+        start = end;
+    }
+}
+
+/// An edit as the run of pieces it replaced and the run it put in their
+/// place, so that it can be undone and redone without a copy of the table.
+struct Change {
+    /// Index of the first piece of both runs.
+    index: usize,
+    before: Vec<Piece>,
+    after: Vec<Piece>,
+    /// Offset the edit was made at.
+    offset: usize,
+}
+
+/// The bytes of the file together with the changes made to them that
+/// haven't been saved yet.
+///
+/// Changes are kept in a piece table: the file itself is never copied, the
+/// contents are described by a list of pieces that point either into the file
+/// or into a buffer that only ever grows. Inserting or deleting therefore
+/// only touches the list of pieces, however large the file is.
+pub struct EditBuffer {
+    source: ByteSource,
+    added: Vec<u8>,
+    pieces: Vec<Piece>,
+    /// Sum of the lengths of the pieces.
+    len: usize,
+    undo_stack: Vec<Change>,
+    redo_stack: Vec<Change>,
+}
+
+impl EditBuffer {
+    pub fn new(source: ByteSource) -> EditBuffer {
+        let pieces = Self::unmodified_pieces(&source);
+        EditBuffer {
+            len: source.len(),
+            source,
+            added: Vec::new(),
+            pieces,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    fn unmodified_pieces(source: &ByteSource) -> Vec<Piece> {
+        match source.len() {
+            0 => Vec::new(),
+            len => vec![Piece::Original { start: 0, len }],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether there are changes that haven't been saved.
+    pub fn is_modified(&self) -> bool {
+        self.pieces != Self::unmodified_pieces(&self.source)
+    }
+
+    /// Iterates over the pieces together with the offset they start at.
+    fn pieces_with_offsets(&self) -> impl Iterator<Item = (usize, &Piece)> {
+        self.pieces.iter().scan(0, |offset, piece| {
+            let start = *offset;
+            *offset += piece.len();
+            Some((start, piece))
+        })
+    }
+
+    /// Returns whether any of the `len` bytes starting at `offset` was
+    /// changed.
+    pub fn is_range_modified(&self, offset: usize, len: usize) -> bool {
+        let end = offset.saturating_add(len);
+        self.pieces_with_offsets()
+            .take_while(|(start, _)| *start < end)
+            .any(|(start, piece)| {
+                matches!(piece, Piece::Added { .. }) && start + piece.len() > offset
+            })
+    }
+
+    fn piece_bytes(&self, piece: &Piece) -> &[u8] {
+        match *piece {
+            Piece::Original { start, len } => self.source.read(start, len),
+            Piece::Added { start, len } => &self.added[start..start + len],
+        }
+    }
+
+    /// Returns at most `len` bytes starting at `offset`, with the changes
+    /// applied. Bytes are only copied if the range spans several pieces.
+    pub fn read(&self, offset: usize, len: usize) -> Cow<'_, [u8]> {
+        let end = offset.saturating_add(len);
+        let mut pieces = self
+            .pieces_with_offsets()
+            .skip_while(|(start, piece)| start + piece.len() <= offset)
+            .take_while(|(start, _)| *start < end)
+            .map(|(start, piece)| {
+                let bytes = self.piece_bytes(piece);
+                let from = offset.saturating_sub(start);
+                let to = (end - start).min(bytes.len());
+                &bytes[from..to]
+            });
+        let Some(first) = pieces.next() else {
+            return Cow::Borrowed(&[]);
+        };
+        match pieces.next() {
+            None => Cow::Borrowed(first),
+            Some(second) => {
+                let mut bytes = first.to_vec();
+                bytes.extend_from_slice(second);
+                pieces.for_each(|piece| bytes.extend_from_slice(piece));
+                Cow::Owned(bytes)
+            }
+        }
+    }
+
+    /// Replaces `len` bytes at `offset` with `bytes`. Only the run of pieces
+    /// around the edit is rebuilt, and it is recorded so that the edit can
+    /// be undone.
+    fn replace(&mut self, offset: usize, len: usize, bytes: &[u8]) {
+        // The run starts with the piece ending at `offset`, if there is one,
+        // so that bytes typed one after another extend the same piece
+        let mut first = None;
+        let mut last = self.pieces.len();
+        for (index, (start, piece)) in self.pieces_with_offsets().enumerate() {
+            match first {
+                None if start + piece.len() >= offset => first = Some((index, start)),
+                Some(_) if start >= offset + len => {
+                    last = index;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let (first, run_start) = first.unwrap_or((self.pieces.len(), self.len));
+        let before = self.pieces[first..last].to_vec();
+        let run_len = before.iter().map(Piece::len).sum();
+
+        let mut after = Vec::with_capacity(before.len() + 1);
+        push_range(&mut after, &before, 0, offset - run_start);
+        push_piece(
+            &mut after,
+            Piece::Added {
+                start: self.added.len(),
+                len: bytes.len(),
+            },
+        );
+        self.added.extend_from_slice(bytes);
+        push_range(&mut after, &before, offset - run_start + len, run_len);
+
+        let change = Change {
+            index: first,
+            before,
+            after,
+            offset,
+        };
+        self.swap_run(&change.before, &change.after, change.index);
+        self.undo_stack.push(change);
+        self.redo_stack.clear();
+    }
+
+    /// Replaces the run `from` starting at the piece at `index` with `to`.
+    fn swap_run(&mut self, from: &[Piece], to: &[Piece], index: usize) {
+        self.pieces
+            .splice(index..index + from.len(), to.iter().copied());
+        self.len = self.len + to.iter().map(Piece::len).sum::<usize>()
+            - from.iter().map(Piece::len).sum::<usize>();
+    }
+
+    /// Replaces the bytes starting at `offset` with `bytes`. Bytes that would
+    /// end up past the end of the file are dropped.
+    pub fn overwrite(&mut self, offset: usize, bytes: &[u8]) {
+        let len = bytes.len().min(self.len().saturating_sub(offset));
+        if len == 0 {
+            return;
+        }
+        self.replace(offset, len, &bytes[..len]);
+    }
+
+    /// Inserts `bytes` before the byte at `offset`, or appends them if
+    /// `offset` is the end of the file.
+    pub fn insert(&mut self, offset: usize, bytes: &[u8]) {
+        if bytes.is_empty() || offset > self.len() {
+            return;
+        }
+        self.replace(offset, 0, bytes);
+    }
+
+    /// Removes up to `len` bytes starting at `offset`.
+    pub fn delete(&mut self, offset: usize, len: usize) {
+        let len = len.min(self.len().saturating_sub(offset));
+        if len == 0 {
+            return;
+        }
+        self.replace(offset, len, &[]);
+    }
+
+    /// Reverts the last edit and returns the offset it was made at.
+    pub fn undo(&mut self) -> Option<usize> {
+        let change = self.undo_stack.pop()?;
+        self.swap_run(&change.after, &change.before, change.index);
+        let offset = change.offset;
+        self.redo_stack.push(change);
+        Some(offset)
+    }
+
+    /// Applies the last undone edit again and returns the offset it was made
+    /// at.
+    pub fn redo(&mut self) -> Option<usize> {
+        let change = self.redo_stack.pop()?;
+        self.swap_run(&change.before, &change.after, change.index);
+        let offset = change.offset;
+        self.undo_stack.push(change);
+        Some(offset)
+    }
+
+    /// Writes the contents to `path`. A temporary file next to it is written
+    /// first and then renamed, so the file is never left half written. Any
+    /// part of the `original` file that wasn't loaded is copied over
+    /// unchanged.
+    pub fn save(&self, path: &Path, original: Option<&Path>) -> io::Result<()> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+        let mut temp_name = file_name.to_os_string();
+        temp_name.push(".byteblitz-tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        let result = self.write_to(path, &temp_path, original);
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    fn write_to(&self, path: &Path, temp_path: &Path, original: Option<&Path>) -> io::Result<()> {
+        let mut temp = File::create(temp_path)?;
+        const CHUNK: usize = 1 << 16;
+        for offset in (0..self.len()).step_by(CHUNK) {
+            temp.write_all(&self.read(offset, CHUNK))?;
+        }
+        if let Some(Ok(mut original)) = original.map(File::open) {
+            original.seek(SeekFrom::Start(self.source.len() as u64))?;
+            io::copy(&mut original, &mut temp)?;
+        }
+        if let Ok(metadata) = fs::metadata(path) {
+            temp.set_permissions(metadata.permissions())?;
+        }
+        temp.sync_all()?;
+        fs::rename(temp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer() -> EditBuffer {
+        EditBuffer::new(ByteSource::Buffered(vec![0, 1, 2, 3, 4, 5]))
+    }
+
+    #[test]
+    fn test_overwrite_and_read() {
+        let mut buffer = buffer();
+        assert!(matches!(buffer.read(0, 3), Cow::Borrowed(_)));
+        buffer.overwrite(4, &[0xAA, 0xBB, 0xCC]);
+        assert_eq!(&*buffer.read(0, 10), &[0, 1, 2, 3, 0xAA, 0xBB]);
+        assert_eq!(&*buffer.read(5, 1), &[0xBB]);
+        assert!(buffer.is_range_modified(3, 2));
+        assert!(!buffer.is_range_modified(0, 4));
+        assert!(matches!(buffer.read(0, 3), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_insert_and_delete() {
+        let mut buffer = buffer();
+        buffer.insert(2, &[0xAA, 0xBB]);
+        assert_eq!(buffer.len(), 8);
+        assert_eq!(&*buffer.read(0, 10), &[0, 1, 0xAA, 0xBB, 2, 3, 4, 5]);
+        buffer.delete(1, 2);
+        assert_eq!(&*buffer.read(0, 10), &[0, 0xBB, 2, 3, 4, 5]);
+        buffer.insert(6, &[0xCC]);
+        buffer.delete(4, 10);
+        assert_eq!(&*buffer.read(0, 10), &[0, 0xBB, 2, 3]);
+        assert_eq!(&*buffer.read(1, 2), &[0xBB, 2]);
+        assert!(buffer.is_range_modified(1, 1));
+        assert!(!buffer.is_range_modified(2, 2));
+
+        buffer.undo();
+        buffer.undo();
+        assert_eq!(&*buffer.read(0, 10), &[0, 0xBB, 2, 3, 4, 5]);
+        buffer.undo();
+        buffer.undo();
+        assert_eq!(&*buffer.read(0, 10), &[0, 1, 2, 3, 4, 5]);
+        assert!(!buffer.is_modified());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut buffer = buffer();
+        buffer.overwrite(1, &[0xAA, 0xAA]);
+        buffer.overwrite(2, &[0xBB]);
+        assert_eq!(&*buffer.read(0, 4), &[0, 0xAA, 0xBB, 3]);
+        assert_eq!(buffer.undo(), Some(2));
+        assert_eq!(&*buffer.read(0, 4), &[0, 0xAA, 0xAA, 3]);
+        assert_eq!(buffer.undo(), Some(1));
+        assert_eq!(&*buffer.read(0, 4), &[0, 1, 2, 3]);
+        assert!(!buffer.is_modified());
+        assert_eq!(buffer.undo(), None);
+        assert_eq!(buffer.redo(), Some(1));
+        assert_eq!(&*buffer.read(0, 4), &[0, 0xAA, 0xAA, 3]);
+        buffer.overwrite(0, &[0xCC]);
+        assert_eq!(buffer.redo(), None);
+    }
+
+    #[test]
+    fn test_typing_extends_a_single_piece() {
+        let mut buffer = buffer();
+        for byte in 0..100 {
+            buffer.insert(2 + usize::from(byte), &[byte]);
+        }
+        for offset in 102..105 {
+            buffer.overwrite(offset, &[0xAA]);
+        }
+        assert_eq!(buffer.len(), 106);
+        assert_eq!(&*buffer.read(100, 6), &[98, 99, 0xAA, 0xAA, 0xAA, 5]);
+        assert_eq!(buffer.pieces.len(), 3);
+        assert!(buffer
+            .undo_stack
+            .iter()
+            .all(|change| change.before.len() <= 2));
+
+        while buffer.undo().is_some() {}
+        assert_eq!(&*buffer.read(0, 10), &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(buffer.len(), 6);
+        assert!(!buffer.is_modified());
+        while buffer.redo().is_some() {}
+        assert_eq!(&*buffer.read(100, 6), &[98, 99, 0xAA, 0xAA, 0xAA, 5]);
+    }
+
+    #[test]
+    fn test_save_keeps_the_part_that_was_not_loaded() {
+        let path = std::env::temp_dir().join(format!("byteblitz-save-{}", std::process::id()));
+        fs::write(&path, [0, 1, 2, 3, 4, 5]).unwrap();
+        let mut buffer = EditBuffer::new(ByteSource::open(&path, Some(4)).unwrap());
+        buffer.overwrite(1, &[0xAA]);
+        buffer.insert(0, &[0xBB]);
+        buffer.save(&path, Some(&path)).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), vec![0xBB, 0, 0xAA, 2, 3, 4, 5]);
+        fs::remove_file(&path).unwrap();
+    }
+}
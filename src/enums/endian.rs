@@ -3,3 +3,13 @@ pub enum Endian {
     Big,
     Little,
 }
+
+impl Endian {
+    /// Returns the opposite byte order.
+    pub fn toggled(self) -> Endian {
+        match self {
+            Endian::Big => Endian::Little,
+            Endian::Little => Endian::Big,
+        }
+    }
+}
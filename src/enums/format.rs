@@ -1,19 +1,80 @@
-use strum_macros::EnumIter; // To derive the iterator functionality.
-#[derive(EnumIter, Debug, Clone, Copy, PartialEq)]
-
+use strum_macros::{EnumIter, EnumString}; // To derive the iterator and parsing functionality.
+#[derive(EnumIter, EnumString, Debug, Clone, Copy, PartialEq)]
+#[strum(ascii_case_insensitive)]
 pub enum Format {
     Hex,
+    #[strum(serialize = "Int8", serialize = "i8")]
     Int8,
+    #[strum(serialize = "Uint8", serialize = "u8")]
     Uint8,
+    #[strum(serialize = "Int16", serialize = "i16")]
     Int16,
+    #[strum(serialize = "Uint16", serialize = "u16")]
     Uint16,
+    #[strum(serialize = "Int24", serialize = "i24")]
     Int24,
+    #[strum(serialize = "Uint24", serialize = "u24")]
     Uint24,
+    #[strum(serialize = "Uint32", serialize = "u32")]
     Uint32,
+    #[strum(serialize = "Int32", serialize = "i32")]
     Int32,
+    #[strum(serialize = "Uint64", serialize = "u64")]
     Uint64,
+    #[strum(serialize = "Int64", serialize = "i64")]
     Int64,
     Ascii,
+    #[strum(serialize = "Latin1", serialize = "latin-1", serialize = "iso-8859-1")]
+    Latin1,
+    Cp437,
+    #[strum(serialize = "Utf8", serialize = "utf-8")]
+    Utf8,
+    #[strum(serialize = "Utf16Le", serialize = "utf-16le")]
+    Utf16Le,
+    #[strum(serialize = "Utf16Be", serialize = "utf-16be")]
+    Utf16Be,
     F32,
     F64,
 }
+
+impl Format {
+    /// Number of bytes a single value of this format is decoded from.
+    pub fn size(&self) -> usize {
+        match self {
+            Format::Hex
+            | Format::Int8
+            | Format::Uint8
+            | Format::Ascii
+            | Format::Latin1
+            | Format::Cp437
+            | Format::Utf8 => 1,
+            Format::Int16 | Format::Uint16 | Format::Utf16Le | Format::Utf16Be => 2,
+            Format::Int24 | Format::Uint24 => 3,
+            Format::Uint32 | Format::Int32 | Format::F32 => 4,
+            Format::Uint64 | Format::Int64 | Format::F64 => 8,
+        }
+    }
+
+    /// Returns whether the format shows characters rather than numbers.
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            Format::Ascii
+                | Format::Latin1
+                | Format::Cp437
+                | Format::Utf8
+                | Format::Utf16Le
+                | Format::Utf16Be
+        )
+    }
+
+    /// Number of bytes after a row that are needed to decode it, since a
+    /// character can be spread over several rows.
+    pub fn lookahead(&self) -> usize {
+        match self {
+            Format::Utf8 => 3,
+            Format::Utf16Le | Format::Utf16Be => 2,
+            _ => 0,
+        }
+    }
+}
@@ -1,4 +1,12 @@
 pub enum InputMode {
     Normal,
     Editing,
+    Search,
+    Strings,
+    Structure,
+    Overwrite,
+    Insert,
+    Command,
+    Visual,
+    Bookmarks,
 }
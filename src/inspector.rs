@@ -0,0 +1,107 @@
+//! Every reading of the bytes at one offset, for the inspector panel.
+
+use crate::{
+    conversion_utils::window::convert_bytes,
+    enums::{endian::Endian, format::Format},
+};
+
+/// The numeric formats the inspector decodes and the names they are listed
+/// under.
+const FORMATS: [(Format, &str); 12] = [
+    (Format::Int8, "i8"),
+    (Format::Uint8, "u8"),
+    (Format::Int16, "i16"),
+    (Format::Uint16, "u16"),
+    (Format::Int24, "i24"),
+    (Format::Uint24, "u24"),
+    (Format::Int32, "i32"),
+    (Format::Uint32, "u32"),
+    (Format::Int64, "i64"),
+    (Format::Uint64, "u64"),
+    (Format::F32, "f32"),
+    (Format::F64, "f64"),
+];
+
+/// Shown for types that need more bytes than the file has left.
+const MISSING: &str = "-";
+
+/// How the bytes at an offset read as one type, in either byte order.
+#[derive(Debug, PartialEq)]
+pub struct Interpretation {
+    pub name: &'static str,
+    pub big: String,
+    pub little: String,
+}
+
+fn decode(bytes: &[u8], format: Format, endian: Endian) -> String {
+    bytes
+        .get(..format.size())
+        .and_then(|bytes| convert_bytes(bytes, format, endian).ok())
+        .and_then(|converted| converted.values.into_iter().next())
+        .unwrap_or_else(|| MISSING.to_string())
+}
+
+/// Reads `bytes`, which start at the inspected offset, as every numeric type
+/// in both byte orders. The first byte is also shown in binary and octal.
+pub fn inspect(bytes: &[u8]) -> Vec<Interpretation> {
+    let mut interpretations: Vec<Interpretation> = FORMATS
+        .iter()
+        .map(|&(format, name)| Interpretation {
+            name,
+            big: decode(bytes, format, Endian::Big),
+            little: decode(bytes, format, Endian::Little),
+        })
+        .collect();
+    let byte = bytes.first();
+    for (name, text) in [
+        ("binary", byte.map(|byte| format!("{byte:08b}"))),
+        ("octal", byte.map(|byte| format!("{byte:03o}"))),
+    ] {
+        let text = text.unwrap_or_else(|| MISSING.to_string());
+        interpretations.push(Interpretation {
+            name,
+            big: text.clone(),
+            little: text,
+        });
+    }
+    interpretations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(interpretations: &'a [Interpretation], name: &str) -> (&'a str, &'a str) {
+        let found = interpretations
+            .iter()
+            .find(|interpretation| interpretation.name == name)
+            .unwrap();
+        (&found.big, &found.little)
+    }
+
+    #[test]
+    fn test_inspect() {
+        let interpretations = inspect(&[0xFF, 0x01, 0x00, 0x00, 0x80, 0x3F]);
+        assert_eq!(find(&interpretations, "i8"), ("-1", "-1"));
+        assert_eq!(find(&interpretations, "u16"), ("65281", "511"));
+        assert_eq!(find(&interpretations, "i24"), ("-65280", "511"));
+        assert_eq!(find(&interpretations, "u32"), ("4278255616", "511"));
+        assert_eq!(find(&interpretations, "u64"), ("-", "-"));
+        assert_eq!(find(&interpretations, "binary"), ("11111111", "11111111"));
+        assert_eq!(find(&interpretations, "octal"), ("377", "377"));
+
+        let interpretations = inspect(&[0x3F, 0x80, 0x00, 0x00]);
+        assert_eq!(find(&interpretations, "f32").0, "1");
+        let interpretations = inspect(&[0, 0, 0, 0, 0, 0, 0xF0, 0x3F]);
+        assert_eq!(find(&interpretations, "f64").1, "1");
+    }
+
+    #[test]
+    fn test_inspect_past_the_end() {
+        let interpretations = inspect(&[]);
+        assert_eq!(interpretations.len(), FORMATS.len() + 2);
+        assert!(interpretations.iter().all(
+            |interpretation| interpretation.big == MISSING && interpretation.little == MISSING
+        ));
+    }
+}
@@ -1,21 +1,69 @@
-use anyhow::Result;
-use config::Config;
+//! byteblitz shows the binary content of files in different formats.
+//!
+//! Besides the terminal interface started by `run`, the crate exposes the
+//! decoding it is built on: [Decoder] turns a byte slice into formatted
+//! values of a [Format], and [FromBytes] decodes single numbers, including
+//! the 24-bit [U24] and [I24]. [Pattern] searches bytes, [Template]
+//! decodes records and [containers] lays out well known file types.
+//!
+//! The interface lives behind the `tui` feature, which is on by default.
+//! Without it the crate doesn't depend on any terminal libraries.
+
+use config::DumpConfig;
 use std::error::Error;
-use ui::core_ui::{generate_ui, shutdown, startup};
+#[cfg(feature = "tui")]
+use {
+    config::Config,
+    ui::core_ui::{generate_ui, shutdown, startup},
+};
 pub mod config;
+#[cfg(feature = "tui")]
 mod ui {
     pub mod core_ui;
     pub mod keyboard_input;
     pub mod ui_helpers;
 }
+#[cfg(feature = "tui")]
 mod app;
+#[cfg(feature = "tui")]
+mod bookmarks;
+mod byte_source;
+#[cfg(feature = "tui")]
+mod clipboard;
+pub mod containers;
 mod conversion_utils;
+mod decoder;
+#[cfg(feature = "tui")]
+mod diff;
+mod dump;
+#[cfg(feature = "tui")]
+mod edit_buffer;
+#[cfg(feature = "tui")]
+mod inspector;
+mod navigation;
+mod search;
+mod template;
 mod enums {
     pub mod endian;
     pub mod format;
+    #[cfg(feature = "tui")]
     pub mod inputmodes;
 }
 
+pub use conversion_utils::{from_bytes::FromBytesError, FromBytes, I24, U24};
+pub use decoder::{Decoder, Value};
+pub use dump::Output;
+pub use enums::{endian::Endian, format::Format};
+pub use search::{Pattern, SearchError};
+pub use template::{Node, Template, TemplateError};
+
+/// Prints the values selected by `config` to stdout without starting the
+/// interface.
+pub fn dump(config: DumpConfig) -> Result<(), Box<dyn Error>> {
+    dump::run(config)
+}
+
+#[cfg(feature = "tui")]
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
     crossterm::terminal::enable_raw_mode()?;
     crossterm::execute!(std::io::stderr(), crossterm::terminal::EnterAlternateScreen)?;
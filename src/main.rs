@@ -1,29 +1,75 @@
-use byteblitz::config::Config;
+use byteblitz::config::{Config, ConfigError, DumpConfig};
 use std::env;
+use std::io::{self, IsTerminal};
 use std::process;
 
 fn print_help_manual() {
-    let help_text = 
+    let help_text =
         "ByteBlitz - Command-line tool for viewing the binary content of files in different formats
 USAGE:
     byteblitz [FILE] [OPTIONS]
+    byteblitz --diff [FILE] [OTHER FILE] [OPTIONS]
+    byteblitz dump [FILE] [DUMP OPTIONS]
 ARGUMENTS:
-    <FILE>    Path to the binary file to be analyzed
+    <FILE>    Path to the binary file to be analyzed, '-' or none reads from a pipe
 OPTIONS:
-    -h, --help      Show this help manual
-    -l, --little    Force little-endian interpretation (overrides system default)
+    -h, --help              Show this help manual
+    -l, --little            Force little-endian interpretation (overrides LITTLE_ENDIAN)
+    -b, --big               Force big-endian interpretation (overrides LITTLE_ENDIAN)
+    -o, --offset <OFFSET>   Start viewing at a byte offset (decimal or 0x prefixed hex)
+    -n, --length <LENGTH>   Only view LENGTH bytes after the offset
+    -f, --format <FORMAT>   Format shown on startup, e.g. hex, u32, i24, f64 or utf8
+    -d, --diff              Compare FILE with a second file side by side
+    -t, --template <FILE>   Decode the records at the cursor with a struct template
+DUMP OPTIONS:
+    Print the values to stdout instead of starting the interface
+    -f, --format <FORMAT>   Format of the printed values (default hex)
+    -e, --endian <ENDIAN>   little or big, -l and -b work as well
+    -o, --offset <OFFSET>   Start at a byte offset (decimal or 0x prefixed hex)
+    -c, --count <COUNT>     Only print COUNT values
+    --output <OUTPUT>       plain (one value per line), csv or json
 SUPPORTED FORMATS:
-    - Hex         : Hexadecimal representation
+    - Hex         : Hexdump with offsets, hexadecimal bytes and printable ASCII
     - Int8/Uint8  : 8-bit integers
     - Int16/Uint16: 16-bit integers
     - Int32/Uint32: 32-bit integers
     - Int64/Uint64: 64-bit integers
     - Float32/64  : 32/64-bit floating point numbers
-    - ASCII       : Printable ASCII characters
+    - ASCII       : Printable ASCII characters, '.' for every other byte
+    - Latin1/Cp437: Single byte character sets
+    - Utf8        : UTF-8 characters, grouped over the bytes they are made of
+    - Utf16Le/Be  : UTF-16 characters in little or big endian
 INTERACTIVE CONTROLS:
-    j/k           : Navigate up/down in the list
+    j/k           : Move the cursor down/up a row, the arrow keys work as well
     h/l           : Switch between formats
-    e             : Enter line number navigation mode
+    b             : Switch between big and little endian
+    w             : Switch the hexdump between 8, 16 and 32 bytes per row
+    </>           : Shift where multi-byte values are decoded from by one byte
+    e             : Go to a byte offset (decimal, 0x hex, or relative +N/-N)
+    /             : Search for text, hex bytes (x:DE AD ?? EF) or values (u32:1337, f32:3.14)
+    n/N           : Jump to the next/previous search match
+    ]c/[c         : Jump to the next/previous difference in diff mode
+    s             : Open the strings panel, Enter jumps to the selected string,
+                    Tab switches between ASCII and UTF-16, +/- change the minimum length
+    R             : Overwrite bytes at the cursor: hex digits in the Hex view, characters
+                    in character views, or a value and Enter in number views
+    i             : Insert bytes before the cursor the same way, Backspace/Del delete them
+    :d N          : Delete N bytes at the cursor
+    v             : Select bytes from the cursor on, d deletes the selection
+    y{h,c,r,b,v}  : Copy the selection, or the value at the cursor, to the clipboard as hex,
+                    a C array, a Rust array, base64 or values of the current format (yy is hex)
+    o             : Show the structure of ELF, PNG, ZIP and WAV files, Enter jumps to the
+                    selected part
+    :export [csv|json] FILE [RANGE]
+                  : Write the bytes of RANGE (START..END or START+LENGTH), the selection or
+                    the whole file to FILE, or their values in the current format as CSV or JSON
+    t             : Show or hide the record decoded by the template, :template FILE loads one
+    m{a-z}/'{a-z} : Set a mark at the cursor / jump back to it
+    :bookmark NAME [COMMENT]
+                  : Bookmark the cursor, B lists the marks and bookmarks, which are kept in
+                    $XDG_DATA_HOME/byteblitz (~/.local/share/byteblitz) for the next time
+    u/U           : Undo/redo the last change
+    :w [FILE]     : Save the changes, to FILE if given, :q! quits without saving them
     q             : Quit the application
 ENVIRONMENT VARIABLES:
     LITTLE_ENDIAN  : Set default endianness to little-endian
@@ -31,25 +77,69 @@ EXAMPLES:
     # Basic usage
     byteblitz binary_file.bin
     # View specific format
-    byteblitz binary_file.bin
-    # Navigate to a specific line
+    byteblitz binary_file.bin --format u32 --little
+    # View 256 bytes starting at offset 0x400
+    byteblitz binary_file.bin --offset 0x400 --length 256
+    # Decode records described by a template, e.g.
+    #   struct Header {
+    #       magic: char[4]
+    #       count: u16 le
+    #       entries: u32[count]
+    #   }
+    byteblitz binary_file.bin --template header.tpl
+    # Print 64 little endian 32-bit values from offset 0x100 as JSON
+    byteblitz dump --format u32 --endian little --offset 0x100 --count 64 --output json file.bin
+    # Inspect the output of another program
+    objcopy -O binary firmware.elf /dev/stdout | byteblitz -
+    # Compare two firmware builds as 32-bit values
+    byteblitz --diff old.bin new.bin --format u32
+    # Navigate to a specific offset
     1. Open byteblitz
     2. Press 'e'
-    3. Enter line number".to_string();
+    3. Enter an offset such as 0x1F40 or +256"
+            .to_string();
 
     println!("{}", help_text);
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    let config = Config::new(&args).unwrap_or_else(|err| {
-        if err == "user needs help" {
+/// Parses the arguments with `parse`, exiting on errors. Without a file,
+/// piped input is read as if `-` had been given.
+fn parse_args<T>(args: &mut Vec<String>, parse: fn(&[String]) -> Result<T, ConfigError>) -> T {
+    let mut result = parse(args);
+    if result.as_ref().err() == Some(&ConfigError::NotEnoughArguments) && !io::stdin().is_terminal()
+    {
+        args.push("-".to_string());
+        result = parse(args);
+    }
+    result.unwrap_or_else(|err| {
+        if err == ConfigError::HelpRequested {
             print_help_manual();
             process::exit(0);
         }
-        println!("Problem parsing arguments: {err}");
+        eprintln!("Problem parsing arguments: {err}");
         process::exit(1);
-    });
+    })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = env::args().collect();
+    if args.get(1).is_some_and(|command| command == "dump") {
+        let config = parse_args(&mut args, DumpConfig::new);
+        if let Err(e) = byteblitz::dump(config) {
+            eprintln!("Application error: {e}");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let config = parse_args(&mut args, Config::new);
+    // The interface reads its keys from the terminal, which can't also be
+    // the input
+    let reads_stdin = config.file_path == "-" || config.diff_path.as_deref() == Some("-");
+    if reads_stdin && io::stdin().is_terminal() {
+        eprintln!("Problem parsing arguments: '-' reads from a pipe, but stdin is a terminal");
+        process::exit(1);
+    }
 
     if let Err(e) = byteblitz::run(config) {
         println!("Application error: {e}");
@@ -0,0 +1,95 @@
+#[cfg(feature = "tui")]
+use std::ops::Range;
+
+/// Target of a "go to" request, as a byte offset.
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Jump {
+    To(usize),
+    Forward(usize),
+    Backward(usize),
+}
+
+#[cfg(feature = "tui")]
+impl Jump {
+    /// Resolves the jump into an absolute byte offset, relative jumps are
+    /// taken from `current`.
+    pub fn resolve(self, current: usize) -> usize {
+        match self {
+            Jump::To(offset) => offset,
+            Jump::Forward(distance) => current.saturating_add(distance),
+            Jump::Backward(distance) => current.saturating_sub(distance),
+        }
+    }
+}
+
+/// Parses a decimal or `0x` prefixed hexadecimal number.
+pub fn parse_offset(value: &str) -> Option<usize> {
+    let value = value.trim();
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// Parses a range of bytes, either from a start to an end offset such as
+/// `0x100..0x180`, or a start and a length such as `0x100+128`.
+#[cfg(feature = "tui")]
+pub fn parse_range(input: &str) -> Option<Range<usize>> {
+    let input = input.trim();
+    if let Some((start, end)) = input.split_once("..") {
+        let (start, end) = (parse_offset(start)?, parse_offset(end)?);
+        return (start <= end).then_some(start..end);
+    }
+    let (start, len) = input.split_once('+')?;
+    let start = parse_offset(start)?;
+    Some(start..start.checked_add(parse_offset(len)?)?)
+}
+
+/// Parses an absolute offset such as `8000` or `0x1F40`, or a relative one
+/// such as `+16` or `-0x10`.
+#[cfg(feature = "tui")]
+pub fn parse_jump(input: &str) -> Option<Jump> {
+    let input = input.trim();
+    if let Some(distance) = input.strip_prefix('+') {
+        return parse_offset(distance).map(Jump::Forward);
+    }
+    if let Some(distance) = input.strip_prefix('-') {
+        return parse_offset(distance).map(Jump::Backward);
+    }
+    parse_offset(input).map(Jump::To)
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jump() {
+        assert_eq!(parse_jump("8000"), Some(Jump::To(8000)));
+        assert_eq!(parse_jump("0x1F40"), Some(Jump::To(0x1F40)));
+        assert_eq!(parse_jump("+16"), Some(Jump::Forward(16)));
+        assert_eq!(parse_jump("-0x10"), Some(Jump::Backward(16)));
+        assert_eq!(parse_jump("0x"), None);
+        assert_eq!(parse_jump("+-1"), None);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("0x100..0x180"), Some(0x100..0x180));
+        assert_eq!(parse_range("16+0x10"), Some(16..32));
+        assert_eq!(parse_range("8..4"), None);
+        assert_eq!(parse_range("8"), None);
+        assert_eq!(parse_range("1+x"), None);
+    }
+
+    #[test]
+    fn test_resolve_jump() {
+        assert_eq!(Jump::To(5).resolve(100), 5);
+        assert_eq!(Jump::Forward(5).resolve(100), 105);
+        assert_eq!(Jump::Backward(500).resolve(100), 0);
+    }
+}
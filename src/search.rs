@@ -0,0 +1,202 @@
+use thiserror::Error;
+
+use crate::{
+    conversion_utils::to_bytes::{value_to_bytes, ToBytesError},
+    enums::{endian::Endian, format::Format},
+};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SearchError {
+    #[error("nothing to search for")]
+    Empty,
+    #[error("'{0}' is not a hex byte or ??")]
+    InvalidHexByte(String),
+    #[error(transparent)]
+    InvalidValue(#[from] ToBytesError),
+}
+
+/// A sequence of bytes to look for, [None] matches any byte.
+#[derive(Debug, PartialEq)]
+pub struct Pattern(Vec<Option<u8>>);
+
+impl Pattern {
+    /// Parses what was typed into the search field:
+    ///
+    /// - `x:DE AD ?? EF` searches for hex bytes, `??` matches any byte
+    /// - `u32:1337`, `f32:3.14` etc. search for a value in the given
+    ///   [endianness](Endian), the type is any name a [Format] can be parsed
+    ///   from
+    /// - `s:text` or anything else is searched for as UTF-8 text, so text with
+    ///   a colon in it only needs the prefix if it starts with a type name
+    pub fn parse(input: &str, endian: Endian) -> Result<Pattern, SearchError> {
+        let bytes = match input.split_once(':') {
+            Some(("x" | "hex", hex)) => Self::parse_hex(hex)?,
+            Some(("s", text)) => text.bytes().map(Some).collect(),
+            Some((kind, value)) => match kind.parse::<Format>() {
+                Ok(format) => value_to_bytes(value, format, endian)?
+                    .into_iter()
+                    .map(Some)
+                    .collect(),
+                Err(_) => input.bytes().map(Some).collect(),
+            },
+            None => input.bytes().map(Some).collect(),
+        };
+        if bytes.is_empty() {
+            return Err(SearchError::Empty);
+        }
+        Ok(Pattern(bytes))
+    }
+
+    fn parse_hex(hex: &str) -> Result<Vec<Option<u8>>, SearchError> {
+        let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+        digits
+            .chunks(2)
+            .map(|pair| {
+                let pair: String = pair.iter().collect();
+                match pair.as_str() {
+                    "??" => Ok(None),
+                    _ if pair.len() == 2 => u8::from_str_radix(&pair, 16)
+                        .map(Some)
+                        .map_err(|_| SearchError::InvalidHexByte(pair)),
+                    _ => Err(SearchError::InvalidHexByte(pair)),
+                }
+            })
+            .collect()
+    }
+
+    /// Number of bytes a match spans. Parsing never returns an empty pattern.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn matches_at(&self, haystack: &[u8], position: usize) -> bool {
+        haystack[position..]
+            .iter()
+            .zip(&self.0)
+            .all(|(byte, expected)| expected.is_none_or(|expected| expected == *byte))
+    }
+
+    /// Returns the offsets of every match in `haystack`, matches may overlap.
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<usize> {
+        if haystack.len() < self.len() {
+            return Vec::new();
+        }
+        (0..=haystack.len() - self.len())
+            .filter(|&position| self.matches_at(haystack, position))
+            .collect()
+    }
+}
+
+/// Offsets found by a search and the one that was jumped to last.
+#[cfg(feature = "tui")]
+pub struct Matches {
+    pub offsets: Vec<usize>,
+    pub len: usize,
+    pub current: usize,
+}
+
+#[cfg(feature = "tui")]
+impl Matches {
+    /// Searches `haystack` and selects the first match at or after `offset`.
+    pub fn find(pattern: &Pattern, haystack: &[u8], offset: usize) -> Matches {
+        let offsets = pattern.find_all(haystack);
+        let current = offsets
+            .iter()
+            .position(|&found| found >= offset)
+            .unwrap_or_default();
+        Matches {
+            offsets,
+            len: pattern.len(),
+            current,
+        }
+    }
+
+    pub fn current_offset(&self) -> Option<usize> {
+        self.offsets.get(self.current).copied()
+    }
+
+    /// Selects the next match, wrapping around at the end of the file.
+    pub fn next(&mut self) -> Option<usize> {
+        if !self.offsets.is_empty() {
+            self.current = (self.current + 1) % self.offsets.len();
+        }
+        self.current_offset()
+    }
+
+    /// Selects the previous match, wrapping around at the start of the file.
+    pub fn previous(&mut self) -> Option<usize> {
+        if !self.offsets.is_empty() {
+            self.current = (self.current + self.offsets.len() - 1) % self.offsets.len();
+        }
+        self.current_offset()
+    }
+
+    /// Returns whether any match overlaps the `len` bytes starting at `start`.
+    pub fn overlaps(&self, start: usize, len: usize) -> bool {
+        let first = self
+            .offsets
+            .partition_point(|&found| found + self.len <= start);
+        self.offsets
+            .get(first)
+            .is_some_and(|&found| found < start + len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patterns() {
+        assert_eq!(
+            Pattern::parse("x:DE ad ?? EF", Endian::Big),
+            Ok(Pattern(vec![Some(0xDE), Some(0xAD), None, Some(0xEF)]))
+        );
+        assert_eq!(
+            Pattern::parse("u16:258", Endian::Little),
+            Ok(Pattern(vec![Some(2), Some(1)]))
+        );
+        assert_eq!(
+            Pattern::parse("héllo", Endian::Big),
+            Ok(Pattern("héllo".bytes().map(Some).collect()))
+        );
+        assert_eq!(
+            Pattern::parse("s:u32:1", Endian::Big),
+            Ok(Pattern("u32:1".bytes().map(Some).collect()))
+        );
+        assert_eq!(
+            Pattern::parse("x:DEA", Endian::Big),
+            Err(SearchError::InvalidHexByte("A".to_string()))
+        );
+        assert_eq!(
+            Pattern::parse("http://", Endian::Big),
+            Ok(Pattern("http://".bytes().map(Some).collect()))
+        );
+        assert_eq!(Pattern::parse("x:", Endian::Big), Err(SearchError::Empty));
+    }
+
+    #[test]
+    fn test_find_all() {
+        let haystack = [0xAA, 0x01, 0xAA, 0xAA, 0x02, 0xAA];
+        let pattern = Pattern::parse("x:AA ??", Endian::Big).unwrap();
+        assert_eq!(pattern.find_all(&haystack), vec![0, 2, 3]);
+        let pattern = Pattern::parse("x:AA AA", Endian::Big).unwrap();
+        assert_eq!(pattern.find_all(&haystack), vec![2]);
+        assert_eq!(pattern.find_all(&[0xAA]), Vec::<usize>::new());
+    }
+
+    #[test]
+    #[cfg(feature = "tui")]
+    fn test_matches_navigation() {
+        let haystack = b"abc abc abc";
+        let pattern = Pattern::parse("abc", Endian::Big).unwrap();
+        let mut matches = Matches::find(&pattern, haystack, 1);
+        assert_eq!(matches.current_offset(), Some(4));
+        assert_eq!(matches.next(), Some(8));
+        assert_eq!(matches.next(), Some(0));
+        assert_eq!(matches.previous(), Some(8));
+        assert!(matches.overlaps(6, 4));
+        assert!(!matches.overlaps(3, 1));
+    }
+}
@@ -0,0 +1,572 @@
+//! Templates describe the layout of a record so that a range of bytes can be
+//! decoded field by field instead of as a single format.
+//!
+//! A template is a list of structs, the first one is the record that gets
+//! decoded:
+//!
+//! ```text
+//! # Lines starting with '#' are comments
+//! struct Header {
+//!     magic: char[4]
+//!     version: u16 le
+//!     count: u8
+//!     entries: Entry[count]
+//! }
+//!
+//! struct Entry {
+//!     id: u24 be
+//!     value: f32
+//! }
+//! ```
+//!
+//! Fields take the number types of [Format] (`u8`..`u64`, `i24`, `f32`, ...),
+//! `char` or the name of another struct. Any type can be turned into an array
+//! with a fixed count or the name of an earlier integer field. Number fields
+//! may end in `le` or `be`, otherwise the current endianness is used.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    conversion_utils::{ascii::convert_to_printable, window::convert_bytes},
+    enums::{endian::Endian, format::Format},
+};
+
+/// Structs can contain each other, this stops templates that do so forever.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TemplateError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("the template doesn't declare any struct")]
+    Empty,
+    #[error("unknown type '{0}'")]
+    UnknownType(String),
+    #[error("'{count}' used as the count of '{field}' isn't an earlier integer field")]
+    InvalidCountField { field: String, count: String },
+    #[error("'{field}' has a count of {value} which isn't a valid count")]
+    InvalidCount { field: String, value: String },
+    #[error("'{0}' at {1:#X} runs past the end of the file")]
+    OutOfBounds(String, usize),
+    #[error("the record reaches {0:#X}, past the bytes that were read")]
+    PastWindow(usize),
+    #[error("structs are nested more than {MAX_DEPTH} levels deep")]
+    TooDeep,
+}
+
+/// A decoded field. Structs and arrays have their fields or elements as
+/// children and no value of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    pub value: String,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /// The node and all its descendants in display order, together with how
+    /// deep they are nested.
+    pub fn flatten(&self) -> Vec<(usize, &Node)> {
+        let mut nodes = Vec::new();
+        self.flatten_into(0, &mut nodes);
+        nodes
+    }
+
+    fn flatten_into<'a>(&'a self, depth: usize, nodes: &mut Vec<(usize, &'a Node)>) {
+        nodes.push((depth, self));
+        for child in &self.children {
+            child.flatten_into(depth + 1, nodes);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum FieldType {
+    Number(Format),
+    Char,
+    Struct(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Count {
+    Fixed(usize),
+    Field(String),
+}
+
+#[derive(Debug, PartialEq)]
+struct Field {
+    name: String,
+    field_type: FieldType,
+    count: Option<Count>,
+    endian: Option<Endian>,
+}
+
+#[derive(Debug, PartialEq)]
+struct StructDef {
+    name: String,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Template {
+    structs: Vec<StructDef>,
+}
+
+fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses a field such as `entries: Entry[count]` or `size: u32 le`.
+fn parse_field(line: &str) -> Result<Field, String> {
+    let (name, rest) = line
+        .split_once(':')
+        .ok_or("expected a field like 'name: type'")?;
+    let name = name.trim();
+    if !is_identifier(name) {
+        return Err(format!("invalid field name '{name}'"));
+    }
+
+    let mut words = rest.trim().trim_end_matches([',', ';']).split_whitespace();
+    let type_word = words.next().ok_or("missing field type")?;
+    let endian = match words.next() {
+        None => None,
+        Some(word) if word.eq_ignore_ascii_case("le") => Some(Endian::Little),
+        Some(word) if word.eq_ignore_ascii_case("be") => Some(Endian::Big),
+        Some(word) => return Err(format!("expected 'le' or 'be', found '{word}'")),
+    };
+    if let Some(word) = words.next() {
+        return Err(format!("unexpected '{word}'"));
+    }
+
+    let (type_name, count) = match type_word.split_once('[') {
+        Some((type_name, count)) => {
+            let count = count
+                .strip_suffix(']')
+                .ok_or("missing ']' after the array count")?;
+            let count = match count.parse() {
+                Ok(count) => Count::Fixed(count),
+                Err(_) if is_identifier(count) => Count::Field(count.to_string()),
+                Err(_) => return Err(format!("invalid array count '{count}'")),
+            };
+            (type_name, Some(count))
+        }
+        None => (type_word, None),
+    };
+
+    let field_type = if type_name == "char" {
+        FieldType::Char
+    } else {
+        match type_name.parse::<Format>() {
+            Ok(format) if format != Format::Hex && !format.is_text() => FieldType::Number(format),
+            _ if is_identifier(type_name) => FieldType::Struct(type_name.to_string()),
+            _ => return Err(format!("invalid type '{type_name}'")),
+        }
+    };
+    if endian.is_some() && !matches!(field_type, FieldType::Number(_)) {
+        return Err("only number fields can have an endianness".to_string());
+    }
+
+    Ok(Field {
+        name: name.to_string(),
+        field_type,
+        count,
+        endian,
+    })
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let mut structs: Vec<StructDef> = Vec::new();
+        let mut current: Option<StructDef> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let syntax = |message: String| TemplateError::Syntax {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line == "}" {
+                let finished = current
+                    .take()
+                    .ok_or_else(|| syntax("'}' without a struct".to_string()))?;
+                if finished.fields.is_empty() {
+                    return Err(syntax(format!("struct '{}' has no fields", finished.name)));
+                }
+                structs.push(finished);
+            } else if let Some(header) = line.strip_prefix("struct ") {
+                if current.is_some() {
+                    return Err(syntax(
+                        "structs can't be declared inside structs".to_string(),
+                    ));
+                }
+                let name = header
+                    .trim()
+                    .strip_suffix('{')
+                    .map(str::trim)
+                    .filter(|name| is_identifier(name))
+                    .ok_or_else(|| syntax("expected 'struct Name {'".to_string()))?;
+                if structs.iter().any(|def| def.name == name) {
+                    return Err(syntax(format!("struct '{name}' is declared twice")));
+                }
+                current = Some(StructDef {
+                    name: name.to_string(),
+                    fields: Vec::new(),
+                });
+            } else {
+                let def = current
+                    .as_mut()
+                    .ok_or_else(|| syntax("fields have to be inside a struct".to_string()))?;
+                let field = parse_field(line).map_err(syntax)?;
+                if let Some(Count::Field(count)) = &field.count {
+                    // Counts can only be read from integers that come first
+                    let is_integer = def.fields.iter().any(|earlier| {
+                        earlier.name == *count
+                            && earlier.count.is_none()
+                            && matches!(earlier.field_type, FieldType::Number(format)
+                                if !matches!(format, Format::F32 | Format::F64))
+                    });
+                    if !is_integer {
+                        return Err(TemplateError::InvalidCountField {
+                            field: field.name,
+                            count: count.clone(),
+                        });
+                    }
+                }
+                def.fields.push(field);
+            }
+        }
+
+        if let Some(unfinished) = current {
+            return Err(TemplateError::Syntax {
+                line: source.lines().count(),
+                message: format!("struct '{}' is missing its '}}'", unfinished.name),
+            });
+        }
+        for field in structs.iter().flat_map(|def| &def.fields) {
+            if let FieldType::Struct(name) = &field.field_type {
+                if !structs.iter().any(|def| def.name == *name) {
+                    return Err(TemplateError::UnknownType(name.clone()));
+                }
+            }
+        }
+        if structs.is_empty() {
+            return Err(TemplateError::Empty);
+        }
+        Ok(Template { structs })
+    }
+
+    /// Decodes the first struct of the template from `bytes`, starting at
+    /// `offset`.
+    pub fn decode(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        endian: Endian,
+    ) -> Result<Node, TemplateError> {
+        let window = &bytes[offset.min(bytes.len())..];
+        self.decode_window(window, offset, bytes.len(), endian)
+    }
+
+    /// Decodes the first struct of the template from the start of `window`,
+    /// the bytes found at `base` in a file of `file_len` bytes. The offsets of
+    /// the nodes are those in the file, so only part of a large file needs to
+    /// be read. A record that doesn't fit in the window fails with
+    /// [TemplateError::PastWindow], telling how far it reaches.
+    pub fn decode_window(
+        &self,
+        window: &[u8],
+        base: usize,
+        file_len: usize,
+        endian: Endian,
+    ) -> Result<Node, TemplateError> {
+        let root = &self.structs[0];
+        let decoder = Decoder {
+            template: self,
+            bytes: window,
+            base,
+            file_len,
+            endian,
+        };
+        let mut node = decoder.decode_struct(root, base, 0)?;
+        node.name = root.name.clone();
+        Ok(node)
+    }
+}
+
+struct Decoder<'a> {
+    template: &'a Template,
+    bytes: &'a [u8],
+    /// Offset of the first of `bytes` in the file.
+    base: usize,
+    file_len: usize,
+    endian: Endian,
+}
+
+impl Decoder<'_> {
+    fn take(&self, name: &str, offset: usize, len: usize) -> Result<&[u8], TemplateError> {
+        let end = offset.saturating_add(len);
+        if end > self.file_len {
+            return Err(TemplateError::OutOfBounds(name.to_string(), offset));
+        }
+        self.bytes
+            .get(offset - self.base..end - self.base)
+            .ok_or(TemplateError::PastWindow(end))
+    }
+
+    fn decode_struct(
+        &self,
+        def: &StructDef,
+        offset: usize,
+        depth: usize,
+    ) -> Result<Node, TemplateError> {
+        if depth > MAX_DEPTH {
+            return Err(TemplateError::TooDeep);
+        }
+        let mut values: HashMap<&str, String> = HashMap::new();
+        let mut children = Vec::new();
+        let mut position = offset;
+
+        for field in &def.fields {
+            let node = match &field.count {
+                None => self.decode_single(field, &field.name, position, depth)?,
+                Some(count) => {
+                    let count = match count {
+                        Count::Fixed(count) => *count,
+                        Count::Field(name) => {
+                            let value = &values[name.as_str()];
+                            value.parse().map_err(|_| TemplateError::InvalidCount {
+                                field: field.name.clone(),
+                                value: value.clone(),
+                            })?
+                        }
+                    };
+                    self.decode_array(field, count, position, depth)?
+                }
+            };
+            position += node.size;
+            values.insert(&field.name, node.value.clone());
+            children.push(node);
+        }
+
+        Ok(Node {
+            name: def.name.clone(),
+            offset,
+            size: position - offset,
+            value: String::new(),
+            children,
+        })
+    }
+
+    fn decode_array(
+        &self,
+        field: &Field,
+        count: usize,
+        offset: usize,
+        depth: usize,
+    ) -> Result<Node, TemplateError> {
+        // Every element takes up at least a byte, a count beyond the bytes
+        // that are left could only fail after decoding a node per byte
+        if count > self.file_len.saturating_sub(offset) {
+            return Err(TemplateError::OutOfBounds(field.name.clone(), offset));
+        }
+        let name = format!("{}[{}]", field.name, count);
+        // Character arrays are shown as a single string
+        if field.field_type == FieldType::Char {
+            let bytes = self.take(&field.name, offset, count)?;
+            return Ok(Node {
+                name,
+                offset,
+                size: count,
+                value: format!(
+                    "\"{}\"",
+                    bytes
+                        .iter()
+                        .map(|&b| convert_to_printable(b))
+                        .collect::<String>()
+                ),
+                children: Vec::new(),
+            });
+        }
+
+        let mut children = Vec::new();
+        let mut position = offset;
+        for index in 0..count {
+            // Elements that take up no bytes at all are only shown once
+            let element = self.decode_single(field, &format!("[{index}]"), position, depth)?;
+            position += element.size;
+            let is_empty = element.size == 0;
+            children.push(element);
+            if is_empty {
+                break;
+            }
+        }
+        Ok(Node {
+            name,
+            offset,
+            size: position - offset,
+            value: String::new(),
+            children,
+        })
+    }
+
+    fn decode_single(
+        &self,
+        field: &Field,
+        name: &str,
+        offset: usize,
+        depth: usize,
+    ) -> Result<Node, TemplateError> {
+        let (size, value, children) = match &field.field_type {
+            FieldType::Number(format) => {
+                let bytes = self.take(name, offset, format.size())?;
+                let endian = field.endian.unwrap_or(self.endian);
+                let value = convert_bytes(bytes, *format, endian)
+                    .ok()
+                    .and_then(|converted| converted.values.into_iter().next())
+                    .unwrap_or_default();
+                (format.size(), value, Vec::new())
+            }
+            FieldType::Char => {
+                let byte = self.take(name, offset, 1)?[0];
+                (1, format!("'{}'", convert_to_printable(byte)), Vec::new())
+            }
+            FieldType::Struct(struct_name) => {
+                let template = self.template;
+                let def = template
+                    .structs
+                    .iter()
+                    .find(|def| def.name == *struct_name)
+                    .ok_or_else(|| TemplateError::UnknownType(struct_name.clone()))?;
+                let node = self.decode_struct(def, offset, depth + 1)?;
+                (node.size, String::new(), node.children)
+            }
+        };
+        Ok(Node {
+            name: name.to_string(),
+            offset,
+            size,
+            value,
+            children,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "
+        # A header followed by its entries
+        struct Header {
+            magic: char[4]
+            version: u16 le
+            count: u8
+            entries: Entry[count]
+        }
+
+        struct Entry {
+            id: u24 be
+            value: i8
+        }
+    ";
+
+    #[test]
+    fn test_decode_nested_record() {
+        let template = Template::parse(TEMPLATE).unwrap();
+        let bytes = [
+            0xFF, b'B', b'L', b'T', b'Z', 0x02, 0x01, 2, 0, 0, 1, 0xFF, 0, 1, 0, 5,
+        ];
+        let root = template.decode(&bytes, 1, Endian::Big).unwrap();
+        assert_eq!(root.name, "Header");
+        assert_eq!(root.size, 15);
+
+        let fields: Vec<(usize, &str, usize, &str)> = root
+            .flatten()
+            .into_iter()
+            .map(|(depth, node)| (depth, node.name.as_str(), node.offset, node.value.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (0, "Header", 1, ""),
+                (1, "magic[4]", 1, "\"BLTZ\""),
+                (1, "version", 5, "258"),
+                (1, "count", 7, "2"),
+                (1, "entries[2]", 8, ""),
+                (2, "[0]", 8, ""),
+                (3, "id", 8, "1"),
+                (3, "value", 11, "-1"),
+                (2, "[1]", 12, ""),
+                (3, "id", 12, "256"),
+                (3, "value", 15, "5"),
+            ]
+        );
+
+        assert_eq!(
+            template.decode(&bytes[..10], 1, Endian::Big),
+            Err(TemplateError::OutOfBounds("id".to_string(), 8))
+        );
+        assert_eq!(
+            template.decode_window(&bytes[1..], 1, bytes.len(), Endian::Big),
+            Ok(root)
+        );
+        assert_eq!(
+            template.decode_window(&bytes[1..10], 1, bytes.len(), Endian::Big),
+            Err(TemplateError::PastWindow(11))
+        );
+    }
+
+    #[test]
+    fn test_counts_beyond_the_bytes_left() {
+        let template = Template::parse("struct A {\n  len: u32\n  data: u8[len]\n}").unwrap();
+        assert_eq!(
+            template.decode(&[0xFF, 0xFF, 0xFF, 0xFF, 1, 2], 0, Endian::Big),
+            Err(TemplateError::OutOfBounds("data".to_string(), 4))
+        );
+        let root = template
+            .decode_window(&[0, 0, 0, 2, 1, 2], 0x1000, 0x1006, Endian::Big)
+            .unwrap();
+        assert_eq!(root.children[1].offset, 0x1004);
+        assert_eq!(root.children[1].children[1].value, "2");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert_eq!(Template::parse("# nothing"), Err(TemplateError::Empty));
+        assert_eq!(
+            Template::parse("struct A {\n  b: B\n}"),
+            Err(TemplateError::UnknownType("B".to_string()))
+        );
+        assert_eq!(
+            Template::parse("struct A {\n  data: u8[len]\n  len: u8\n}"),
+            Err(TemplateError::InvalidCountField {
+                field: "data".to_string(),
+                count: "len".to_string()
+            })
+        );
+        assert_eq!(
+            Template::parse("struct A {\n  x: u32 middle\n}"),
+            Err(TemplateError::Syntax {
+                line: 2,
+                message: "expected 'le' or 'be', found 'middle'".to_string()
+            })
+        );
+        let recursive = Template::parse("struct A {\n  a: A\n}").unwrap();
+        assert_eq!(
+            recursive.decode(&[0; 64], 0, Endian::Big),
+            Err(TemplateError::TooDeep)
+        );
+    }
+}
@@ -1,31 +1,31 @@
 use crate::{
-    app::App,
+    app::{App, DiffPanel, WINDOW_ROWS},
+    bookmarks::Bookmarks,
+    byte_source::ByteSource,
     config::Config,
-    conversion_utils::{
-        ascii::convert_bytes_to_ascii,
-        from_one_byte_to_i8::add_byte_as_i8,
-        hexadecimal::convert_bytes_to_hex,
-        three_byte_numbers::{I24, U24},
-        FromBytes,
-    },
+    diff::Diff,
+    edit_buffer::EditBuffer,
     enums::{endian::Endian, format::Format, inputmodes::InputMode},
+    template::Template,
 };
 use anyhow::Result;
 use crossterm::{
-    execute,
+    event, execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
-    layout::{Constraint, Layout},
+    layout::{Constraint, Direction, Layout},
     prelude::{CrosstermBackend, Frame, Terminal},
+    widgets::ListState,
 };
-use std::error::Error;
-use std::fs;
+use std::{error::Error, time::Duration};
 use strum::IntoEnumIterator;
 
 use super::ui_helpers::{
-    create_converted_values_list, create_endianess_paragraph, create_help_message,
-    create_input_paragraph, create_instructions_paragraph, create_list_of_formats, update,
+    create_bookmarks_list, create_converted_values_list, create_diff_list,
+    create_endianess_paragraph, create_help_message, create_input_paragraph, create_inspector_list,
+    create_instructions_paragraph, create_status_paragraph, create_strings_list,
+    create_structure_list, create_template_list, update, INSPECTOR_HEIGHT,
 };
 
 pub fn startup() -> Result<()> {
@@ -42,78 +42,86 @@ pub fn shutdown() -> Result<()> {
 pub fn generate_ui(config: Config) -> Result<(), Box<dyn Error>> {
     let mut t = Terminal::new(CrosstermBackend::new(std::io::stderr()))?;
 
-    let bytes_read = fs::read(config.file_path)?;
+    let limit = config
+        .length
+        .map(|length| config.offset.saturating_add(length));
+    let buffer = EditBuffer::new(ByteSource::open(&config.file_path, limit)?);
     let mut endianess = Endian::Big;
-    let mut u32_numbers = Vec::new();
-    let mut i32_numbers = Vec::new();
-    let mut converted_binary_to_hex = Vec::new();
-    let mut i8_numbers = Vec::new();
-    let mut u16_numbers = Vec::new();
-    let mut i16_numbers = Vec::new();
-    let mut u24_numbers = Vec::new();
-    let mut i24_numbers = Vec::new();
-    let mut u64_numbers = Vec::new();
-    let mut i64_numbers = Vec::new();
-    let mut f32_numbers = Vec::new();
-    let mut f64_numbers = Vec::new();
-    let mut converted_binary_to_ascii = Vec::new();
     let format_list: Vec<Format> = Format::iter().collect();
     if config.little_endianess {
         endianess = Endian::Little;
     }
 
-    let _ = (
-        u32::add_bytes(&bytes_read, endianess, &mut u32_numbers),
-        i32::add_bytes(&bytes_read, endianess, &mut i32_numbers),
-        u16::add_bytes(&bytes_read, endianess, &mut u16_numbers),
-        i16::add_bytes(&bytes_read, endianess, &mut i16_numbers),
-        U24::add_bytes(&bytes_read, endianess, &mut u24_numbers),
-        I24::add_bytes(&bytes_read, endianess, &mut i24_numbers),
-        u64::add_bytes(&bytes_read, endianess, &mut u64_numbers),
-        i64::add_bytes(&bytes_read, endianess, &mut i64_numbers),
-        f32::add_bytes(&bytes_read, endianess, &mut f32_numbers),
-        f64::add_bytes(&bytes_read, endianess, &mut f64_numbers),
-    );
+    let format_list_index = format_list
+        .iter()
+        .position(|format| *format == config.format)
+        .unwrap_or_default();
 
-    add_byte_as_i8(&bytes_read, &mut i8_numbers)?;
-    convert_bytes_to_ascii(&bytes_read, &mut converted_binary_to_ascii)?;
-    convert_bytes_to_hex(&bytes_read, &mut converted_binary_to_hex)?;
-    let vec = &u32_numbers.clone();
     let mut app = App {
-        bytes_read,
+        buffer,
+        file_path: config.file_path,
         should_quit: false,
         endianess,
-        converted_binary_to_u32: u32_numbers,
-        converted_binary_to_i32: i32_numbers,
-        converted_binary_to_hex,
-        converted_binary_to_i8: i8_numbers,
-        converted_binary_to_u16: u16_numbers,
-        converted_binary_to_i16: i16_numbers,
-        converted_binary_to_u24: u24_numbers,
-        converted_binary_to_i24: i24_numbers,
-        converted_binary_to_u64: u64_numbers,
-        converted_binary_to_i64: i64_numbers,
-        converted_binary_to_f32: f32_numbers,
-        converted_binary_to_f64: f64_numbers,
-        converted_binary_to_ascii,
         start_of_window: 0,
-        end_of_window: 30,
-        current_format: Format::Hex,
-        format_list_index: 0,
+        end_of_window: WINDOW_ROWS,
+        current_format: config.format,
+        format_list_index,
         format_list,
         input: String::new(),
         input_mode: InputMode::Normal,
         cursor_position: 0,
-        max_length: vec.len(),
+        bytes_per_row: 16,
+        // Values are decoded from the requested offset even if it isn't
+        // aligned to their size
+        phase: config.offset,
+        search_matches: None,
+        status_message: None,
+        strings_panel: None,
+        byte_cursor: 0,
+        selection_anchor: None,
+        pending_nibble: None,
+        diff_panel: None,
+        pending_key: None,
+        template: None,
+        show_template: false,
+        template_record: None,
+        structure_panel: None,
+        panels_stale: false,
+        bookmarks: Bookmarks::default(),
+        bookmarks_pending: false,
+        bookmarks_panel: None,
     };
+    app.load_bookmarks();
+    if let Some(template_path) = &config.template_path {
+        let template = Template::parse(&std::fs::read_to_string(template_path)?)
+            .map_err(|e| format!("{template_path}: {e}"))?;
+        app.template = Some(template);
+        app.show_template = true;
+    }
+    if let Some(diff_path) = config.diff_path {
+        let source = ByteSource::open(&diff_path, limit)?;
+        let diff = Diff::new(&app.buffer.read(0, app.buffer.len()), source.as_slice());
+        app.status_message = Some(diff.summary());
+        app.diff_panel = Some(DiffPanel {
+            file_path: diff_path,
+            source,
+            diff,
+            current: None,
+        });
+    }
+    app.go_to(config.offset);
 
     loop {
         t.draw(|f| {
             ui(&mut app, f);
         })?;
 
-        // application update
+        // application update, keys that are already waiting such as pasted
+        // text are handled before drawing again
         update(&mut app)?;
+        while !app.should_quit && event::poll(Duration::ZERO)? {
+            update(&mut app)?;
+        }
 
         // application exit
         if app.should_quit {
@@ -125,10 +133,15 @@ pub fn generate_ui(config: Config) -> Result<(), Box<dyn Error>> {
 }
 
 fn ui(app: &mut App, f: &mut Frame) {
+    app.refresh_panels();
+    if app.show_template {
+        app.refresh_template_record();
+    }
+    // The inspector is as tall as its rows, the values take up what is left
     let constraints = [
-        Constraint::Percentage(25),
+        Constraint::Length(INSPECTOR_HEIGHT),
         Constraint::Percentage(5),
-        Constraint::Percentage(50),
+        Constraint::Min(0),
         Constraint::Percentage(5),
         Constraint::Percentage(4),
         Constraint::Percentage(5),
@@ -139,20 +152,74 @@ fn ui(app: &mut App, f: &mut Frame) {
         .split(f.size());
 
     let list = create_converted_values_list(app);
-    let current_format_paragraph = create_list_of_formats(app);
+    let inspector = create_inspector_list(app);
     let instructions_paragraph = create_instructions_paragraph();
     let help_message = create_help_message(app);
     let input = create_input_paragraph(app);
     let endianess_paragraph = create_endianess_paragraph(app);
+    let status_paragraph = create_status_paragraph(app);
 
-    f.render_widget(current_format_paragraph, layout[0]);
+    f.render_widget(inspector, layout[0]);
     f.render_widget(endianess_paragraph, layout[1]);
-    f.render_widget(list, layout[2]);
+    // The side panels share a column next to the values
+    let side_panels = usize::from(app.strings_panel.is_some())
+        + usize::from(app.bookmarks_panel.is_some())
+        + usize::from(app.structure_panel.is_some())
+        + usize::from(app.show_template);
+    let values_area = if side_panels > 0 {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(layout[2]);
+        let side = Layout::default()
+            .constraints(vec![Constraint::Ratio(1, side_panels as u32); side_panels])
+            .split(panes[1]);
+        let mut side = side.iter().copied();
+        if let Some(panel) = &app.strings_panel {
+            let mut state = ListState::default().with_selected(Some(panel.selected));
+            f.render_stateful_widget(create_strings_list(app), side.next().unwrap(), &mut state);
+        }
+        if let Some(panel) = &app.bookmarks_panel {
+            let mut state = ListState::default().with_selected(Some(panel.selected));
+            f.render_stateful_widget(create_bookmarks_list(app), side.next().unwrap(), &mut state);
+        }
+        if let Some(panel) = &app.structure_panel {
+            let mut state = ListState::default().with_selected(Some(panel.selected));
+            f.render_stateful_widget(create_structure_list(app), side.next().unwrap(), &mut state);
+        }
+        if app.show_template {
+            f.render_widget(create_template_list(app), side.next().unwrap());
+        }
+        panes[0]
+    } else {
+        layout[2]
+    };
+    // The diff list selects the same row, which keeps both lists scrolled
+    // alike when the cursor is below the bottom of the area
+    let cursor = app.cursor_row().checked_sub(app.start_of_window);
+    let mut state = ListState::default().with_selected(cursor);
+    match &app.diff_panel {
+        Some(_) => {
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(values_area);
+            f.render_stateful_widget(list, panes[0], &mut state.clone());
+            f.render_stateful_widget(create_diff_list(app), panes[1], &mut state);
+        }
+        None => f.render_stateful_widget(list, values_area, &mut state),
+    }
     f.render_widget(instructions_paragraph, layout[3]);
     f.render_widget(help_message, layout[5]);
     f.render_widget(input, layout[4]);
+    f.render_widget(status_paragraph, layout[6]);
 
-    if let InputMode::Editing = app.input_mode {
+    if let InputMode::Editing
+    | InputMode::Search
+    | InputMode::Command
+    | InputMode::Overwrite
+    | InputMode::Insert = app.input_mode
+    {
         f.set_cursor(
             layout[4].x + app.cursor_position as u16 + 1,
             layout[4].y + 1,
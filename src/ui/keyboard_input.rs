@@ -1,49 +1,140 @@
-use crate::{app::App, enums::inputmodes::InputMode};
+use std::io::Write;
+
+use crate::{
+    app::App,
+    clipboard::{osc52, Representation},
+    enums::inputmodes::InputMode,
+};
 use crossterm::event::KeyCode;
 
+/// Copies the bytes at the cursor, or the selection, in the representation
+/// picked by the key typed after `y`.
+fn yank(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
+    let representation = match key {
+        KeyCode::Char('y' | 'h') => Representation::Hex,
+        KeyCode::Char('c') => Representation::CArray,
+        KeyCode::Char('r') => Representation::RustArray,
+        KeyCode::Char('b') => Representation::Base64,
+        KeyCode::Char('v') => Representation::Values(app.current_format, app.endianess),
+        _ => return Ok(()),
+    };
+    let text = app.yank(representation);
+    // The terminal is drawn on stderr, so that is where it reads the sequence
+    let mut stderr = std::io::stderr();
+    stderr.write_all(osc52(&text).as_bytes())?;
+    stderr.flush()
+}
+
 pub fn handle_normal_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
+    if let Some(first) = app.pending_key.take() {
+        match (first, key) {
+            (']', KeyCode::Char('c')) => app.jump_to_difference(true),
+            ('[', KeyCode::Char('c')) => app.jump_to_difference(false),
+            ('y', key) => yank(app, key)?,
+            ('m', KeyCode::Char(letter)) if letter.is_ascii_alphabetic() => app.set_mark(letter),
+            ('\'', KeyCode::Char(letter)) if letter.is_ascii_alphabetic() => {
+                app.jump_to_mark(letter)
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
     match key {
         KeyCode::Char('q') => {
-            app.should_quit = true;
+            app.quit(false);
+        }
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.move_byte_cursor(app.byte_cursor + app.row_size());
         }
-        KeyCode::Char('j') => {
-            app.start_of_window += 1;
-            app.end_of_window += 1;
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.move_byte_cursor(app.byte_cursor.saturating_sub(app.row_size()));
         }
-        KeyCode::Char('k') if app.start_of_window > 0 => {
-            app.start_of_window -= 1;
-            app.end_of_window -= 1;
+        KeyCode::Left => {
+            app.move_byte_cursor(app.byte_cursor.saturating_sub(app.value_size()));
+        }
+        KeyCode::Right => {
+            app.move_byte_cursor(app.byte_cursor + app.value_size());
         }
         KeyCode::Char('h') if app.format_list_index > 0 => {
-            app.format_list_index -= 1;
-            app.current_format = app.format_list[app.format_list_index];
-            app.start_of_window = 0;
-            app.end_of_window = 30;
+            app.select_format(app.format_list_index - 1);
         }
         KeyCode::Char('l') if app.format_list_index < app.format_list.len() - 1 => {
-            app.format_list_index += 1;
-            app.current_format = app.format_list[app.format_list_index];
-            app.start_of_window = 0;
-            app.end_of_window = 30;
+            app.select_format(app.format_list_index + 1);
+        }
+        KeyCode::Char('b') => {
+            // Values are decoded when drawn, so every view follows right away
+            app.endianess = app.endianess.toggled();
+        }
+        KeyCode::Char('<') => {
+            app.shift_phase(false);
+        }
+        KeyCode::Char('>') => {
+            app.shift_phase(true);
+        }
+        KeyCode::Char('w') => {
+            app.cycle_bytes_per_row();
         }
         KeyCode::Char('e') => {
             app.input_mode = InputMode::Editing;
         }
+        KeyCode::Char('s') => {
+            app.toggle_strings_panel();
+        }
+        KeyCode::Char('t') => {
+            app.toggle_template_panel();
+        }
+        KeyCode::Char('o') => {
+            app.toggle_structure_panel();
+        }
+        KeyCode::Char('R') => {
+            app.enter_overwrite_mode(false);
+        }
+        KeyCode::Char('i') => {
+            app.enter_overwrite_mode(true);
+        }
+        KeyCode::Char('v') => {
+            app.enter_visual_mode();
+        }
+        KeyCode::Char('u') => {
+            app.undo();
+        }
+        KeyCode::Char('U') => {
+            app.redo();
+        }
+        KeyCode::Char(':') => {
+            app.input_mode = InputMode::Command;
+        }
+        KeyCode::Char('/') => {
+            app.input_mode = InputMode::Search;
+        }
         KeyCode::Char('n') => {
-            return Ok(());
+            app.jump_to_match(true);
+        }
+        KeyCode::Char('N') => {
+            app.jump_to_match(false);
+        }
+        KeyCode::Char('B') => {
+            app.toggle_bookmarks_panel();
+        }
+        KeyCode::Char(first @ (']' | '[' | 'y' | 'm' | '\'')) => {
+            app.pending_key = Some(first);
         }
         _ => {}
     }
     Ok(())
 }
 
-// Function to handle key presses in editing mode
+// Function to handle key presses in editing, search and command mode
 pub fn handle_editing_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
     match key {
-        KeyCode::Enter => {
-            if !app.input.trim().is_empty() {
-                app.submit_message();
+        KeyCode::Enter if !app.input.trim().is_empty() => {
+            match app.input_mode {
+                InputMode::Search => app.submit_search(),
+                InputMode::Command => app.run_command(),
+                _ => app.submit_message(),
             }
+            // A selection made before the command was typed ends with it
+            app.selection_anchor = None;
         }
         KeyCode::Char(to_insert) => {
             app.enter_char(to_insert);
@@ -59,6 +150,174 @@ pub fn handle_editing_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::
         }
         KeyCode::Esc => {
             app.input_mode = InputMode::Normal;
+            app.selection_anchor = None;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Function to handle key presses while the strings panel is focused
+pub fn handle_strings_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.select_string(true);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.select_string(false);
+        }
+        KeyCode::Enter => {
+            app.jump_to_selected_string();
+        }
+        KeyCode::Tab => {
+            app.cycle_string_encoding();
+        }
+        KeyCode::Char('+') => {
+            app.change_min_string_length(true);
+        }
+        KeyCode::Char('-') => {
+            app.change_min_string_length(false);
+        }
+        KeyCode::Char('s') | KeyCode::Esc => {
+            app.toggle_strings_panel();
+        }
+        KeyCode::Char('q') => {
+            app.quit(false);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Function to handle key presses while the structure panel is focused
+pub fn handle_structure_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.select_node(true);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.select_node(false);
+        }
+        KeyCode::Enter => {
+            app.jump_to_selected_node();
+        }
+        KeyCode::Char('o') | KeyCode::Esc => {
+            app.toggle_structure_panel();
+        }
+        KeyCode::Char('q') => {
+            app.quit(false);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Function to handle key presses while the bookmarks panel is focused
+pub fn handle_bookmarks_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.select_bookmark(true);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.select_bookmark(false);
+        }
+        KeyCode::Enter => {
+            app.jump_to_selected_bookmark();
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            app.delete_selected_bookmark();
+        }
+        KeyCode::Char('B') | KeyCode::Esc => {
+            app.toggle_bookmarks_panel();
+        }
+        KeyCode::Char('q') => {
+            app.quit(false);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Function to handle key presses while selecting bytes
+pub fn handle_visual_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
+    if app.pending_key.take().is_some() {
+        return yank(app, key);
+    }
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.move_byte_cursor(app.byte_cursor + app.row_size());
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.move_byte_cursor(app.byte_cursor.saturating_sub(app.row_size()));
+        }
+        KeyCode::Char('h') | KeyCode::Left => {
+            app.move_byte_cursor(app.byte_cursor.saturating_sub(app.value_size()));
+        }
+        KeyCode::Char('l') | KeyCode::Right => {
+            app.move_byte_cursor(app.byte_cursor + app.value_size());
+        }
+        KeyCode::Char('d') => {
+            app.delete_selection();
+        }
+        KeyCode::Char('y') => {
+            app.pending_key = Some('y');
+        }
+        KeyCode::Char(':') => {
+            // The selection is kept for commands such as export
+            app.input_mode = InputMode::Command;
+        }
+        KeyCode::Char('v') | KeyCode::Esc => {
+            app.leave_visual_mode();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Function to handle key presses while overwriting or inserting bytes
+pub fn handle_overwrite_mode_keys(app: &mut App, key: KeyCode) -> Result<(), std::io::Error> {
+    // Number formats move by whole values, everything else by bytes
+    let step = app.value_size();
+    match key {
+        KeyCode::Esc => {
+            app.pending_nibble = None;
+            app.input.clear();
+            app.reset_cursor();
+            app.input_mode = InputMode::Normal;
+            // Only insert mode can put the cursor after the last byte
+            app.move_byte_cursor(app.byte_cursor);
+        }
+        KeyCode::Enter if !app.input.trim().is_empty() => {
+            app.submit_value();
+        }
+        KeyCode::Backspace
+            if app.input.is_empty()
+                && app.pending_nibble.is_none()
+                && matches!(app.input_mode, InputMode::Insert) =>
+        {
+            let offset = app.byte_cursor.saturating_sub(step);
+            app.delete_bytes(offset, app.byte_cursor - offset);
+        }
+        KeyCode::Backspace => {
+            app.delete_char();
+        }
+        KeyCode::Delete => {
+            app.delete_bytes(app.byte_cursor, step);
+        }
+        KeyCode::Left => {
+            app.move_byte_cursor(app.byte_cursor.saturating_sub(step));
+        }
+        KeyCode::Right => {
+            app.move_byte_cursor(app.byte_cursor + step);
+        }
+        KeyCode::Up => {
+            app.move_byte_cursor(app.byte_cursor.saturating_sub(app.row_size()));
+        }
+        KeyCode::Down => {
+            app.move_byte_cursor(app.byte_cursor + app.row_size());
+        }
+        KeyCode::Char(to_insert) => {
+            app.overwrite_char(to_insert);
         }
         _ => {}
     }
@@ -1,15 +1,31 @@
 use crate::{
     app::App,
-    enums::{format::Format, inputmodes::InputMode},
+    conversion_utils::{
+        hexadecimal::{convert_to_hex, hexdump_line},
+        window::convert_bytes,
+    },
+    enums::{endian::Endian, format::Format, inputmodes::InputMode},
+    inspector::inspect,
+    template::Node,
 };
 use crossterm::event::{self};
 use ratatui::{
     style::{Color, Modifier, Style, Stylize},
-    text::{Line, Text},
-    widgets::{Block, Borders, List, ListDirection, ListItem, Paragraph},
+    text::{Line, Span, Text},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, List, ListItem, Paragraph,
+    },
 };
 
-use super::keyboard_input::{handle_editing_mode_keys, handle_normal_mode_keys};
+use super::keyboard_input::{
+    handle_bookmarks_mode_keys, handle_editing_mode_keys, handle_normal_mode_keys,
+    handle_overwrite_mode_keys, handle_strings_mode_keys, handle_structure_mode_keys,
+    handle_visual_mode_keys,
+};
+
+const DIFFERENCE_STYLE: Style = Style::new().fg(Color::Black).bg(Color::Cyan);
+const SELECTION_STYLE: Style = Style::new().fg(Color::Black).bg(Color::LightBlue);
 
 pub fn update(app: &mut App) -> Result<(), std::io::Error> {
     if event::poll(std::time::Duration::from_millis(250))? {
@@ -17,7 +33,16 @@ pub fn update(app: &mut App) -> Result<(), std::io::Error> {
             if key.kind == event::KeyEventKind::Press {
                 match app.input_mode {
                     InputMode::Normal => handle_normal_mode_keys(app, key.code)?,
-                    InputMode::Editing => handle_editing_mode_keys(app, key.code)?,
+                    InputMode::Editing | InputMode::Search | InputMode::Command => {
+                        handle_editing_mode_keys(app, key.code)?
+                    }
+                    InputMode::Overwrite | InputMode::Insert => {
+                        handle_overwrite_mode_keys(app, key.code)?
+                    }
+                    InputMode::Strings => handle_strings_mode_keys(app, key.code)?,
+                    InputMode::Structure => handle_structure_mode_keys(app, key.code)?,
+                    InputMode::Visual => handle_visual_mode_keys(app, key.code)?,
+                    InputMode::Bookmarks => handle_bookmarks_mode_keys(app, key.code)?,
                 }
             }
         }
@@ -27,83 +52,337 @@ pub fn update(app: &mut App) -> Result<(), std::io::Error> {
 
 pub fn create_display_list<T: std::fmt::Display>(
     vector_to_be_converted: &[T],
-    app: &mut App,
+    app: &App,
 ) -> Vec<String> {
     let max_index = app.start_of_window + app.end_of_window;
     let max_index_width = max_index.to_string().len();
-    app.max_length = vector_to_be_converted.len();
 
     vector_to_be_converted
         .iter()
         .enumerate() // Get the index and value
-        .skip(app.start_of_window) // Skip to the starting window index.
-        .take(app.end_of_window - app.start_of_window) // Take the range from start to end of the window.
-        .map(|(index, n)| format!("{:width$}. {}", index + 1, n, width = max_index_width))
+        .map(|(index, n)| {
+            format!(
+                "{:width$}. {}",
+                app.start_of_window + index + 1,
+                n,
+                width = max_index_width
+            )
+        })
         .collect()
 }
 
-pub fn create_converted_values_list(app: &mut App) -> List<'static> {
-    let converted_values = match app.current_format {
-        Format::Ascii => create_display_list(&app.converted_binary_to_ascii.clone(), app),
-        Format::Uint32 => create_display_list(&app.converted_binary_to_u32.clone(), app),
-        Format::Int32 => create_display_list(&app.converted_binary_to_i32.clone(), app),
-        Format::Int8 => create_display_list(&app.converted_binary_to_i8.clone(), app),
-        Format::Uint8 => create_display_list(&app.bytes_read.clone(), app),
-        Format::Int16 => create_display_list(&app.converted_binary_to_i16.clone(), app),
-        Format::Uint16 => create_display_list(&app.converted_binary_to_u16.clone(), app),
-        Format::Int24 => create_display_list(&app.converted_binary_to_u24.clone(), app),
-        Format::Uint24 => create_display_list(&app.converted_binary_to_i24.clone(), app),
-        Format::Uint64 => create_display_list(&app.converted_binary_to_u64.clone(), app),
-        Format::Int64 => create_display_list(&app.converted_binary_to_i64.clone(), app),
-        Format::F32 => create_display_list(&app.converted_binary_to_f32.clone(), app),
-        Format::F64 => create_display_list(&app.converted_binary_to_f64.clone(), app),
-        Format::Hex => create_display_list(&app.converted_binary_to_hex.clone(), app),
+/// Lays the visible bytes out like `xxd`, with an offset column, the bytes in
+/// hexadecimal and an ASCII gutter.
+pub fn create_hexdump_list(app: &App, bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(app.bytes_per_row)
+        .enumerate()
+        .map(|(index, bytes)| {
+            hexdump_line(
+                app.row_offset(app.start_of_window + index),
+                bytes,
+                app.bytes_per_row,
+            )
+        })
+        .collect()
+}
+
+/// Rows of the visible `bytes` in the current format, and whether the last
+/// one holds the trailing bytes.
+fn create_rows(app: &App, bytes: &[u8]) -> (Vec<String>, bool) {
+    let mut has_trailing_row = false;
+    let rows = match app.current_format {
+        Format::Hex => create_hexdump_list(app, bytes),
+        format => match convert_bytes(bytes, format, app.endianess) {
+            Ok(mut converted) => {
+                // The bytes that don't make up a whole value get a row of
+                // their own instead of disappearing from the view
+                if !converted.trailing.is_empty() {
+                    let hex_strings: Vec<String> = converted
+                        .trailing
+                        .iter()
+                        .map(|&b| convert_to_hex(b))
+                        .collect();
+                    converted.values.push(format!(
+                        "{} <- {} trailing bytes",
+                        hex_strings.join(" "),
+                        converted.trailing.len()
+                    ));
+                    has_trailing_row = true;
+                }
+                // Drop the rows of the lookahead bytes
+                let rows = app.end_of_window.saturating_sub(app.start_of_window);
+                if converted.values.len() > rows {
+                    converted.values.truncate(rows);
+                    has_trailing_row = false;
+                }
+                create_display_list(&converted.values, app)
+            }
+            Err(e) => vec![e.to_string()],
+        },
     };
+    (rows, has_trailing_row)
+}
+
+pub fn create_converted_values_list(app: &App) -> List<'static> {
+    let (converted_values, has_trailing_row) = create_rows(app, &app.visible_bytes());
+    let row_count = converted_values.len();
+    let items: Vec<ListItem> = converted_values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let item = ListItem::new(value);
+            let row = app.start_of_window + index;
+            if has_trailing_row && index == row_count - 1 {
+                item.style(Style::default().fg(Color::Red))
+            } else if app.row_is_selected(row) {
+                item.style(SELECTION_STYLE)
+            } else if app.row_has_match(row) {
+                item.style(Style::default().fg(Color::Black).bg(Color::Yellow))
+            } else if app.row_differs(row) {
+                item.style(DIFFERENCE_STYLE)
+            } else if app
+                .buffer
+                .is_range_modified(app.row_offset(row), app.row_size())
+            {
+                item.style(Style::default().fg(Color::LightRed))
+            } else {
+                item
+            }
+        })
+        .collect();
 
-    List::new(converted_values)
+    let selection = match app.selection() {
+        Some(selection) => format!(
+            " - Selected {} bytes at {:#X}",
+            selection.len(),
+            selection.start
+        ),
+        None => String::new(),
+    };
+    let mut block = Block::default()
+        .title(format!(
+            "Converted binary values - Total: {} - Decoded from byte {} - Cursor at {:#X}{}{}",
+            app.max_length(),
+            app.decode_base(),
+            app.byte_cursor,
+            selection,
+            if app.buffer.is_modified() {
+                " - Modified"
+            } else {
+                ""
+            }
+        ))
+        .borders(Borders::ALL);
+    let trailing_bytes = app.trailing_bytes();
+    if trailing_bytes > 0 {
+        block = block.title(
+            Title::from(format!(
+                " {} trailing bytes at the end of the file don't make up a whole {:?} ",
+                trailing_bytes, app.current_format
+            ))
+            .position(Position::Bottom),
+        );
+    }
+
+    List::new(items)
+        .block(block)
+        .style(Style::default().fg(Color::Green))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">>")
+        .repeat_highlight_symbol(true)
+}
+
+/// Second file of diff mode, scrolled along with the first one.
+pub fn create_diff_list(app: &App) -> List<'static> {
+    let Some(panel) = app.diff_panel.as_ref() else {
+        return List::new(Vec::<ListItem>::new());
+    };
+    let (rows, has_trailing_row) = create_rows(app, app.visible_diff_bytes());
+    let row_count = rows.len();
+    let items: Vec<ListItem> = rows
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let item = ListItem::new(value);
+            if has_trailing_row && index == row_count - 1 {
+                item.style(Style::default().fg(Color::Red))
+            } else if app.row_differs(app.start_of_window + index) {
+                item.style(DIFFERENCE_STYLE)
+            } else {
+                item
+            }
+        })
+        .collect();
+
+    List::new(items)
         .block(
             Block::default()
-                .title(format!(
-                    "Converted binary values - Total: {}",
-                    app.max_length
-                ))
+                .title(format!("{} - {}", panel.file_path, panel.diff.summary()))
                 .borders(Borders::ALL),
         )
         .style(Style::default().fg(Color::Green))
-        .highlight_style(Style::default().add_modifier(Modifier::ITALIC))
-        .highlight_symbol(">>")
-        .repeat_highlight_symbol(true)
 }
 
-pub fn create_list_of_formats(app: &App) -> List<'static> {
-    let mut vector_of_formats: Vec<ListItem> = Vec::new();
+/// A field of a decoded record or file structure, indented by its depth.
+fn create_node_line(depth: usize, node: &Node) -> String {
+    let value = match node.value.as_str() {
+        "" => String::new(),
+        value => format!(" = {value}"),
+    };
+    format!(
+        "{:indent$}{}{} @{:#X} ({} bytes)",
+        "",
+        node.name,
+        value,
+        node.offset,
+        node.size,
+        indent = depth * 2
+    )
+}
 
-    for (index, element) in app.format_list.iter().enumerate() {
-        if index == app.format_list_index {
-            let format_paragraph = ListItem::new(Text::raw(format!("{:?}", element)))
-                .style(Style::default().fg(Color::Yellow));
+/// Structure of a recognized file type, Enter jumps to the selected node.
+pub fn create_structure_list(app: &App) -> List<'static> {
+    let Some(panel) = app.structure_panel.as_ref() else {
+        return List::new(Vec::<ListItem>::new());
+    };
+    let items: Vec<ListItem> = panel
+        .root
+        .flatten()
+        .into_iter()
+        .map(|(depth, node)| ListItem::new(create_node_line(depth, node)))
+        .collect();
 
-            vector_of_formats.push(format_paragraph);
-        } else {
-            let format_paragraph = ListItem::new(Text::raw(format!("{:?}", element)))
-                .style(Style::default().fg(Color::White));
+    List::new(items)
+        .block(
+            Block::default()
+                .title(format!("Structure - {}", panel.root.name))
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan))
+}
 
-            vector_of_formats.push(format_paragraph);
-        }
-    }
-    let list = List::new(vector_of_formats)
+/// Record decoded by the template at the cursor, one row per field.
+pub fn create_template_list(app: &App) -> List<'static> {
+    let offset = app.byte_cursor;
+    let record = app.template_record.as_ref().map(|record| &record.result);
+    let items: Vec<ListItem> = match record {
+        Some(Ok(root)) => root
+            .flatten()
+            .into_iter()
+            .map(|(depth, node)| ListItem::new(create_node_line(depth, node)))
+            .collect(),
+        Some(Err(e)) => vec![ListItem::new(e.to_string()).style(Style::default().fg(Color::Red))],
+        None => Vec::new(),
+    };
+
+    List::new(items)
         .block(
             Block::default()
-                .title("Current format")
+                .title(format!("Template at {offset:#X}"))
                 .borders(Borders::ALL),
         )
+        .style(Style::default().fg(Color::Cyan))
+}
+
+pub fn create_bookmarks_list(app: &App) -> List<'static> {
+    let items: Vec<ListItem> = app
+        .bookmarks
+        .entries()
+        .into_iter()
+        .map(|entry| {
+            let mut line = format!("{:08X} {}", entry.offset, entry.label);
+            if !entry.comment.is_empty() {
+                line.push_str(&format!(" - {}", entry.comment));
+            }
+            ListItem::new(line)
+        })
+        .collect();
+
+    List::new(items)
+        .block(Block::default().title("Bookmarks").borders(Borders::ALL))
         .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().add_modifier(Modifier::ITALIC))
-        .highlight_symbol(">>")
-        .repeat_highlight_symbol(true)
-        .direction(ListDirection::TopToBottom);
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan))
+}
+
+pub fn create_strings_list(app: &App) -> List<'static> {
+    let Some(panel) = app.strings_panel.as_ref() else {
+        return List::new(Vec::<ListItem>::new());
+    };
+    let items: Vec<ListItem> = panel
+        .strings
+        .iter()
+        .map(|found| ListItem::new(format!("{:08X} {}", found.offset, found.text)))
+        .collect();
+
+    List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Strings - {:?}, at least {} characters",
+                    panel.encoding, panel.min_length
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan))
+}
+
+/// Rows of the inspector: a header, every interpretation and the borders.
+pub const INSPECTOR_HEIGHT: u16 = 17;
+
+/// Longest value shown in a column of the inspector, longer ones such as
+/// tiny floats are cut off.
+const INSPECTOR_COLUMN_WIDTH: usize = 24;
+
+fn inspector_column(text: &str) -> String {
+    if text.chars().count() > INSPECTOR_COLUMN_WIDTH {
+        let cut: String = text.chars().take(INSPECTOR_COLUMN_WIDTH - 1).collect();
+        format!("{cut}…")
+    } else {
+        format!("{text:>INSPECTOR_COLUMN_WIDTH$}")
+    }
+}
+
+/// Every reading of the bytes at the cursor, with the byte order that is
+/// currently selected highlighted.
+pub fn create_inspector_list(app: &App) -> List<'static> {
+    let offset = app.byte_cursor;
+    let bytes = app.buffer.read(offset, 8);
+    let selected = Style::default().fg(Color::Yellow);
+    let (big_style, little_style) = match app.endianess {
+        Endian::Big => (selected, Style::default()),
+        Endian::Little => (Style::default(), selected),
+    };
+    let header = ListItem::new(Line::from(vec![
+        Span::raw(format!("{:<6} ", "type")),
+        Span::styled(inspector_column("big endian"), big_style),
+        Span::raw(" "),
+        Span::styled(inspector_column("little endian"), little_style),
+    ]))
+    .style(Style::default().add_modifier(Modifier::BOLD));
+    let items: Vec<ListItem> = std::iter::once(header)
+        .chain(inspect(&bytes).into_iter().map(|interpretation| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<6} ", interpretation.name)),
+                Span::styled(inspector_column(&interpretation.big), big_style),
+                Span::raw(" "),
+                Span::styled(inspector_column(&interpretation.little), little_style),
+            ]))
+        }))
+        .collect();
 
-    list
+    List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Inspector at {offset:#X} - Current format {:?}",
+                    app.current_format
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White))
 }
 
 pub fn create_endianess_paragraph(app: &App) -> Paragraph<'_> {
@@ -113,7 +392,7 @@ pub fn create_endianess_paragraph(app: &App) -> Paragraph<'_> {
 }
 pub fn create_instructions_paragraph() -> Paragraph<'static> {
     Paragraph::new(Text::raw(
-        "Use 'j' to move down, 'k' to move up in the list. Use 'h' and 'l' to switch between formats",
+        "Use 'j' and 'k' or the arrow keys to move the cursor. Use 'h' and 'l' to switch between formats, 'b' to switch endianess and 'w' to change the hexdump width. Use '<' and '>' to shift multi-byte values by a byte. Use 'n' and 'N' to go to the next and previous search match",
     ))
     .style(Style::default().fg(Color::Blue))
     .block(Block::default().title("Instructions").borders(Borders::ALL))
@@ -126,7 +405,15 @@ pub fn create_help_message(app: &App) -> Paragraph<'static> {
                 "q".bold(),
                 " to exit, ".into(),
                 "e".bold(),
-                " to type in a line number to navigate to.".into(),
+                " to type in a byte offset to navigate to, ".into(),
+                "/".bold(),
+                " to search, ".into(),
+                "s".bold(),
+                " to list strings, ".into(),
+                "R".bold(),
+                " to overwrite bytes, ".into(),
+                "v".bold(),
+                " to select bytes.".into(),
             ],
             Style::default().add_modifier(Modifier::RAPID_BLINK),
         ),
@@ -136,7 +423,104 @@ pub fn create_help_message(app: &App) -> Paragraph<'static> {
                 "Esc".bold(),
                 " to turn off search field, ".into(),
                 "Enter".bold(),
-                " to go to the line number".into(),
+                " to go to the offset (e.g. 8000, 0x1F40, +16 or -0x10)".into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Strings => (
+            vec![
+                "Press ".into(),
+                "Enter".bold(),
+                " to go to the string, ".into(),
+                "Tab".bold(),
+                " to switch encoding, ".into(),
+                "+/-".bold(),
+                " to change the minimum length, ".into(),
+                "Esc".bold(),
+                " to close the panel".into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Structure => (
+            vec![
+                "Press ".into(),
+                "Enter".bold(),
+                " to go to the selected part of the file, ".into(),
+                "Esc".bold(),
+                " to close the panel".into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Overwrite => (
+            vec![
+                "Press ".into(),
+                "Esc".bold(),
+                " to stop editing, arrow keys to move the cursor. Type hex digits, characters or a value followed by "
+                    .into(),
+                "Enter".bold(),
+                " to overwrite, ".into(),
+                "Del".bold(),
+                " to delete".into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Insert => (
+            vec![
+                "Press ".into(),
+                "Esc".bold(),
+                " to stop inserting, arrow keys to move the cursor. Type hex digits, characters or a value followed by "
+                    .into(),
+                "Enter".bold(),
+                " to insert before the cursor, ".into(),
+                "Backspace/Del".bold(),
+                " to delete".into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Bookmarks => (
+            vec![
+                "Press ".into(),
+                "Enter".bold(),
+                " to go to the bookmark, ".into(),
+                "d".bold(),
+                " to delete it, ".into(),
+                "Esc".bold(),
+                " to close the panel".into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Visual => (
+            vec![
+                "Press ".into(),
+                "Esc".bold(),
+                " to stop selecting, ".into(),
+                "j/k/h/l".bold(),
+                " or the arrow keys to extend the selection, ".into(),
+                "d".bold(),
+                " to delete the selected bytes, ".into(),
+                "y".bold(),
+                " and h, c, r, b or v to copy them as hex, C array, Rust array, base64 or values"
+                    .into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Command => (
+            vec![
+                "Press ".into(),
+                "Esc".bold(),
+                " to cancel, ".into(),
+                "Enter".bold(),
+                " to run the command (w, q, q!, wq, d N, template FILE, export [csv|json] FILE [RANGE], bookmark NAME [COMMENT])".into(),
+            ],
+            Style::default(),
+        ),
+        InputMode::Search => (
+            vec![
+                "Press ".into(),
+                "Esc".bold(),
+                " to turn off search field, ".into(),
+                "Enter".bold(),
+                " to search for text, hex bytes (x:DE AD ?? EF) or values (u32:1337)".into(),
             ],
             Style::default(),
         ),
@@ -147,11 +531,30 @@ pub fn create_help_message(app: &App) -> Paragraph<'static> {
 }
 
 // Function to create the input paragraph
-pub fn create_input_paragraph(app: &App) -> Paragraph {
+pub fn create_input_paragraph(app: &App) -> Paragraph<'_> {
+    let title = match app.input_mode {
+        InputMode::Search => "Search",
+        InputMode::Command => "Command",
+        InputMode::Overwrite | InputMode::Insert => "Value",
+        _ => "Byte offset",
+    };
     Paragraph::new(app.input.as_str())
         .style(match app.input_mode {
-            InputMode::Normal => Style::default(),
-            InputMode::Editing => Style::default().fg(Color::Yellow),
+            InputMode::Normal
+            | InputMode::Strings
+            | InputMode::Structure
+            | InputMode::Visual
+            | InputMode::Bookmarks => Style::default(),
+            InputMode::Editing
+            | InputMode::Search
+            | InputMode::Command
+            | InputMode::Overwrite
+            | InputMode::Insert => Style::default().fg(Color::Yellow),
         })
-        .block(Block::default().borders(Borders::ALL).title("Line number"))
+        .block(Block::default().borders(Borders::ALL).title(title))
+}
+
+pub fn create_status_paragraph(app: &App) -> Paragraph<'_> {
+    Paragraph::new(app.status_message.as_deref().unwrap_or_default())
+        .style(Style::default().fg(Color::Magenta))
 }